@@ -1,9 +1,16 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
 use anyhow::Result;
-use clap::Parser;
+use clap::{Args, Parser};
 
-use tetris_ml::Config;
+use tetris_ml::{
+    Budget, Config, Crossover, MctsConfig, MlpConfig, MlpInput, Mutation, NormalizationMode,
+    Optimizer, SearchConfig, Selection,
+};
 
-use tetris_heuristics::used_heuristics::get_heuristics;
+use tetris_heuristics::used_heuristics::DEFAULT_HEURISTICS;
+use tetris_heuristics::HeuristicRegistry;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -22,13 +29,231 @@ pub struct CliArgs {
         help = "How many populations that aren't improving the best entity are allowed"
     )]
     pub max_non_progress_populations: Option<usize>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_values_t = DEFAULT_HEURISTICS.map(String::from),
+        help = "Comma separated names of heuristics the agents use."
+    )]
+    pub heuristics: Vec<String>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Normalization of heuristics: none, static, z-score or min-max. \
+                Either one for all heuristics, or a comma separated mode for each of them."
+    )]
+    pub normalization: Vec<NormalizationMode>,
+    #[arg(
+        long,
+        default_value = "1",
+        help = "Lookahead of agents as depth[:beam_width][:expectimax[=samples]|:peek], \
+                e.g. 3:8:expectimax. A depth past the 2 known pieces needs expectimax or peek."
+    )]
+    pub search: SearchConfig,
+    #[command(flatten)]
+    pub mcts: MctsArgs,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Evolve a neural network with hidden layers of these comma separated sizes \
+                instead of a weight per heuristic, e.g. 16,8."
+    )]
+    pub hidden_layers: Vec<usize>,
+    #[arg(
+        long,
+        help = "Input of the network: heuristics or column-heights. \
+                Implies a network, linear if --hidden-layers aren't given."
+    )]
+    pub network_input: Option<MlpInput>,
+    #[arg(
+        long,
+        default_value = "ga",
+        help = "Optimizer of the weights: ga, cem[:elite_fraction[:noise]] (noisy cross-entropy) \
+                or cma-es[:sigma], e.g. cem:0.2:0.5."
+    )]
+    pub optimizer: Optimizer,
+    #[arg(
+        long,
+        default_value = "roulette",
+        help = "Selection of parents by the genetic algorithm: roulette, tournament[:k], rank \
+                or truncation[:fraction], e.g. tournament:5."
+    )]
+    pub selection: Selection,
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Number of best agents copied to the next generation unchanged."
+    )]
+    pub elite: usize,
+    #[arg(
+        long,
+        default_value = "blend",
+        help = "Crossover of the genetic algorithm: blend, uniform, one-point or sbx[:eta], \
+                e.g. sbx:5."
+    )]
+    pub crossover: Crossover,
+    #[arg(
+        long,
+        default_value = "uniform",
+        help = "Mutation of the genetic algorithm: uniform (one weight), gaussian[:sigma], \
+                per-gene[:sigma] or self-adaptive[:sigma], e.g. gaussian:0.2."
+    )]
+    pub mutation: Mutation,
+    #[arg(
+        long,
+        help = "Keep weights evolved by the genetic algorithm on the unit sphere."
+    )]
+    pub normalize_weights: bool,
+    #[arg(
+        long,
+        default_value = "best_model.json",
+        help = "Where to save weights of the best agent."
+    )]
+    pub output: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct PlayArgs {
+    #[arg(
+        long,
+        help = "Model saved by the training, the built-in one is used if not set."
+    )]
+    pub model: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with = "model",
+        help = "Play with the published El-Tetris weights."
+    )]
+    pub el_tetris: bool,
+    #[arg(
+        long,
+        default_value = "1",
+        help = "Initial lookahead as depth[:beam_width][:expectimax[=samples]|:peek], \
+                the depth can be changed with arrow keys. \
+                A depth past the 2 known pieces needs expectimax or peek."
+    )]
+    pub search: SearchConfig,
+    #[command(flatten)]
+    pub budget: BudgetArgs,
+    #[command(flatten)]
+    pub mcts: MctsArgs,
+}
+
+/// Thinking budget of every move, the search is deepened iteratively while it lasts.
+#[derive(Args, Debug, Clone)]
+pub struct BudgetArgs {
+    #[arg(
+        long,
+        help = "Stop deepening the search after this many milliseconds per move."
+    )]
+    pub think_ms: Option<u64>,
+    #[arg(
+        long,
+        conflicts_with = "think_ms",
+        help = "Stop deepening the search after evaluating this many boards per move."
+    )]
+    pub think_nodes: Option<usize>,
+}
+
+impl BudgetArgs {
+    #[must_use]
+    pub const fn budget(&self) -> Budget {
+        match (self.think_ms, self.think_nodes) {
+            (Some(ms), _) => Budget::Time(Duration::from_millis(ms)),
+            (None, Some(nodes)) => Budget::Nodes(nodes),
+            (None, None) => Budget::Unlimited,
+        }
+    }
+}
+
+/// Monte Carlo tree search used instead of `--search` when any budget is given.
+#[derive(Args, Debug, Clone)]
+pub struct MctsArgs {
+    #[arg(
+        long,
+        help = "Choose moves with MCTS running this many simulations per move."
+    )]
+    pub mcts_iterations: Option<usize>,
+    #[arg(
+        long,
+        help = "Choose moves with MCTS thinking this many milliseconds per move."
+    )]
+    pub mcts_time_ms: Option<u64>,
+    #[arg(
+        long,
+        default_value_t = 2,
+        help = "Greedy moves played by every MCTS rollout."
+    )]
+    pub mcts_rollout_depth: usize,
+}
+
+impl MctsArgs {
+    #[must_use]
+    pub fn config(&self) -> Option<MctsConfig> {
+        if self.mcts_iterations.is_none() && self.mcts_time_ms.is_none() {
+            return None;
+        }
+
+        Some(MctsConfig {
+            iterations: self.mcts_iterations.unwrap_or(usize::MAX),
+            time_limit: self.mcts_time_ms.map(Duration::from_millis),
+            rollout_depth: self.mcts_rollout_depth,
+            ..MctsConfig::default()
+        })
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct ReportArgs {
+    #[arg(long, default_value_t = 10, help = "Number of games the agent plays.")]
+    pub games: usize,
+    #[arg(long, default_value_t = 300, help = "Maximum moves of every game.")]
+    pub moves: usize,
+    #[arg(long, default_value_t = 0, help = "Seed of the first game.")]
+    pub seed: u64,
+    #[arg(
+        long,
+        help = "Model saved by the training, El-Tetris plays if not set."
+    )]
+    pub model: Option<PathBuf>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma separated names of heuristics to report, all registered ones if not set."
+    )]
+    pub heuristics: Vec<String>,
+    #[arg(
+        long,
+        default_value_t = 20,
+        help = "Number of histogram bins of every heuristic."
+    )]
+    pub bins: usize,
+    #[arg(
+        long,
+        default_value_t = 0.9,
+        help = "Print pairs of heuristics correlated at least this much."
+    )]
+    pub threshold: f64,
+    #[arg(
+        long,
+        default_value = "heuristics_report",
+        help = "Directory for distributions.csv, correlations.csv and report.json."
+    )]
+    pub output_dir: PathBuf,
 }
 
 impl TryFrom<CliArgs> for Config {
     type Error = anyhow::Error;
 
     fn try_from(args: CliArgs) -> Result<Self> {
-        let heuristics_used = get_heuristics();
+        let heuristics_used = HeuristicRegistry::default().select(&args.heuristics)?;
+
+        let normalization = match args.normalization[..] {
+            [mode] => vec![mode; heuristics_used.len()],
+            _ => args.normalization,
+        };
 
         Ok(Self {
             n_entities: args.n_entities,
@@ -37,6 +262,21 @@ impl TryFrom<CliArgs> for Config {
             max_populations: args.max_populations,
             max_non_progress_populations: args.max_non_progress_populations,
             heuristics_used,
+            normalization,
+            search: args.search,
+            mcts: args.mcts.config(),
+            network: (!args.hidden_layers.is_empty() || args.network_input.is_some()).then(|| {
+                MlpConfig {
+                    input: args.network_input.unwrap_or_default(),
+                    hidden_layers: args.hidden_layers,
+                }
+            }),
+            optimizer: args.optimizer,
+            selection: args.selection,
+            elite: args.elite,
+            crossover: args.crossover,
+            mutation: args.mutation,
+            normalize_weights: args.normalize_weights,
         })
     }
 }
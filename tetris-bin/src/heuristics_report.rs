@@ -0,0 +1,43 @@
+use std::fs;
+
+use anyhow::Result;
+use clap::Parser;
+use tetris_bin::args::ReportArgs;
+use tetris_bin::report::HeuristicsReport;
+use tetris_heuristics::HeuristicRegistry;
+use tetris_ml::{sample_states_with, Agent, SavedModel};
+
+fn main() -> Result<()> {
+    let args = ReportArgs::parse();
+
+    let registry = HeuristicRegistry::default();
+    let evaluator = match &args.model {
+        Some(path) => SavedModel::load(path)?.to_evaluator(&registry)?,
+        None => Box::new(Agent::el_tetris()),
+    };
+
+    let heuristics = if args.heuristics.is_empty() {
+        registry.all().to_vec()
+    } else {
+        registry.select(&args.heuristics)?
+    };
+
+    println!("Sampling boards from {} games...", args.games);
+    let states = sample_states_with(&evaluator, args.games, args.moves, args.seed);
+
+    println!("Evaluating heuristics on {} boards...", states.len());
+    let report = HeuristicsReport::new(&heuristics, &states, args.bins);
+
+    fs::create_dir_all(&args.output_dir)?;
+    report.write_distributions_csv(args.output_dir.join("distributions.csv"))?;
+    report.write_correlations_csv(args.output_dir.join("correlations.csv"))?;
+    report.save_json(args.output_dir.join("report.json"))?;
+
+    println!("Pairs correlated at least {}:", args.threshold);
+    for (a, b, r) in report.redundant_pairs(args.threshold) {
+        println!("{r:>7.3}  {a} ~ {b}");
+    }
+    println!("Report saved to {}", args.output_dir.display());
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
 pub mod args;
 pub mod meshgrid;
 pub mod persistance;
+pub mod report;
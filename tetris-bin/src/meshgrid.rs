@@ -3,7 +3,7 @@ use indicatif::{ParallelProgressIterator, ProgressStyle};
 use itertools::Itertools;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use tetris_core::game::Game;
-use tetris_heuristics::Heuristic;
+use tetris_heuristics::HeuristicRef;
 use tetris_ml::Agent;
 
 fn linspace(start: f32, stop: f32, num: usize) -> Vec<f32> {
@@ -34,15 +34,15 @@ pub fn generate_grid(
     from: f32,
     to: f32,
     n_samples: usize,
-    x_heuristic: Heuristic,
-    y_heuristic: Heuristic,
+    x_heuristic: &HeuristicRef,
+    y_heuristic: &HeuristicRef,
     max_drops: usize,
 ) -> Result<Vec<FitnessAtPoint>> {
     let mut locations: Vec<(f32, f32)> = Vec::with_capacity(n_samples.pow(2));
 
     for (x, y) in linspace(from, to, n_samples)
         .into_iter()
-        .cartesian_product(linspace(from, to, n_samples).into_iter())
+        .cartesian_product(linspace(from, to, n_samples))
     {
         println!("({x}, {y})");
         locations.push((x, y));
@@ -53,12 +53,12 @@ pub fn generate_grid(
         .map(|(x, y)| {
             println!("Agent with weights ({x}. {y})");
             let mut entity =
-            Agent::from_weights(vec![x, y], &[x_heuristic, y_heuristic])?;
+            Agent::from_weights(vec![x, y], &[x_heuristic.clone(), y_heuristic.clone()])?;
 
             let mut mean_fitness = 0.0;
             for _ in 0..N_TRIES {
                 entity.game = Game::new();
-                entity = entity.play_for_n_turns_or_lose(Some(max_drops), tetris_ml::BranchingMode::Current);
+                entity = entity.play_for_n_turns_or_lose(Some(max_drops), tetris_ml::SearchConfig::GREEDY);
 
                 mean_fitness += entity.fitness();
             }
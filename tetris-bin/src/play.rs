@@ -1,5 +1,9 @@
 use anyhow::Result;
+use clap::Parser;
 use macroquad::window::Conf;
+use tetris_bin::args::PlayArgs;
+use tetris_heuristics::presets::EL_TETRIS;
+use tetris_ml::SavedModel;
 
 fn window_conf() -> Conf {
     Conf {
@@ -14,6 +18,13 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() -> Result<()> {
-    tetris_ui::run().await?;
+    let args = PlayArgs::parse();
+    let model = if args.el_tetris {
+        Some(SavedModel::from_named_weights(&EL_TETRIS))
+    } else {
+        args.model.map(SavedModel::load).transpose()?
+    };
+
+    tetris_ui::run(model, args.search, args.budget.budget(), args.mcts.config()).await?;
     Ok(())
 }
@@ -0,0 +1,286 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
+use tetris_core::game::Game;
+use tetris_heuristics::prelude::*;
+
+/// Distribution of a single heuristic component over the sampled boards.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentStats {
+    pub name: String,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub p5: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p95: f64,
+    pub max: f64,
+    /// Number of samples in equally wide bins spanning `min..=max`.
+    pub histogram: Vec<usize>,
+}
+
+impl ComponentStats {
+    #[must_use]
+    pub fn new(name: String, values: &[f64], n_bins: usize) -> Self {
+        let n = values.len().max(1) as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let min = sorted.first().copied().unwrap_or_default();
+        let max = sorted.last().copied().unwrap_or_default();
+
+        let mut histogram = vec![0; n_bins];
+        if n_bins > 0 {
+            let width = (max - min) / n_bins as f64;
+            for &value in values {
+                let bin = if width > 0. {
+                    ((value - min) / width) as usize
+                } else {
+                    0
+                };
+                histogram[bin.min(n_bins - 1)] += 1;
+            }
+        }
+
+        Self {
+            name,
+            mean,
+            std_dev: variance.sqrt(),
+            min,
+            p5: quantile(&sorted, 0.05),
+            p25: quantile(&sorted, 0.25),
+            median: quantile(&sorted, 0.5),
+            p75: quantile(&sorted, 0.75),
+            p95: quantile(&sorted, 0.95),
+            max,
+            histogram,
+        }
+    }
+}
+
+/// Linearly interpolated quantile of already sorted values.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.;
+    }
+
+    let pos = q * (sorted.len() - 1) as f64;
+    let (lower, upper) = (pos.floor() as usize, pos.ceil() as usize);
+    (sorted[upper] - sorted[lower]).mul_add(pos - pos.floor(), sorted[lower])
+}
+
+/// Pearson correlation, `None` when either of the components is constant.
+fn correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+
+    let (mut covariance, mut var_a, mut var_b) = (0., 0., 0.);
+    for (x, y) in a.iter().zip(b) {
+        let (dx, dy) = (x - mean_a, y - mean_b);
+        covariance += dx * dy;
+        var_a += dx * dx;
+        var_b += dy * dy;
+    }
+
+    (var_a > 0. && var_b > 0.).then(|| covariance / (var_a * var_b).sqrt())
+}
+
+/// Distributions of heuristic components over sampled boards and correlations between them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeuristicsReport {
+    pub n_samples: usize,
+    pub components: Vec<ComponentStats>,
+    /// Pearson correlation of every pair of components, in the order of `components`.
+    pub correlations: Vec<Vec<Option<f64>>>,
+}
+
+impl HeuristicsReport {
+    /// Evaluate every component of `heuristics` on `states`.
+    #[must_use]
+    pub fn new(heuristics: &[HeuristicRef], states: &[Game], n_bins: usize) -> Self {
+        let samples: Vec<Vec<HeuristicScore>> = states
+            .par_iter()
+            .map(|game| evaluate_components(heuristics, &EvalContext::from_game(game)))
+            .collect();
+
+        let columns = (0..total_dimension(heuristics))
+            .map(|idx| samples.iter().map(|s| f64::from(s[idx])).collect())
+            .collect::<Vec<Vec<f64>>>();
+
+        // Components of vector heuristics are prefixed, so that e.g. `holes` stays unambiguous.
+        let names = heuristics
+            .iter()
+            .flat_map(|h| {
+                (0..h.dimension()).map(move |idx| match h.dimension() {
+                    1 => h.component_name(idx),
+                    _ => format!("{}.{}", h.name(), h.component_name(idx)),
+                })
+            })
+            .collect();
+
+        Self::from_columns(names, &columns, n_bins)
+    }
+
+    /// Build the report from values of every component, one column per name.
+    #[must_use]
+    pub fn from_columns(names: Vec<String>, columns: &[Vec<f64>], n_bins: usize) -> Self {
+        let components = names
+            .into_iter()
+            .zip(columns)
+            .map(|(name, values)| ComponentStats::new(name, values, n_bins))
+            .collect();
+
+        let correlations = columns
+            .iter()
+            .map(|a| columns.iter().map(|b| correlation(a, b)).collect())
+            .collect();
+
+        Self {
+            n_samples: columns.first().map_or(0, Vec::len),
+            components,
+            correlations,
+        }
+    }
+
+    /// Pairs of distinct components whose absolute correlation is at least `threshold`,
+    /// most correlated first.
+    #[must_use]
+    pub fn redundant_pairs(&self, threshold: f64) -> Vec<(&str, &str, f64)> {
+        let mut pairs = Vec::new();
+        for (i, row) in self.correlations.iter().enumerate() {
+            for (j, &r) in row.iter().enumerate().skip(i + 1) {
+                if let Some(r) = r.filter(|r| r.abs() >= threshold) {
+                    pairs.push((
+                        self.components[i].name.as_str(),
+                        self.components[j].name.as_str(),
+                        r,
+                    ));
+                }
+            }
+        }
+
+        pairs.sort_by(|a, b| b.2.abs().total_cmp(&a.2.abs()));
+        pairs
+    }
+
+    /// Write one row of summary statistics per component.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file can't be written.
+    pub fn write_distributions_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "name,mean,std_dev,min,p5,p25,median,p75,p95,max")?;
+
+        for c in &self.components {
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{},{},{},{}",
+                c.name, c.mean, c.std_dev, c.min, c.p5, c.p25, c.median, c.p75, c.p95, c.max
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the correlation matrix with component names as the header and the first column,
+    /// leaving cells of constant components empty.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file can't be written.
+    pub fn write_correlations_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let names: Vec<&str> = self.components.iter().map(|c| c.name.as_str()).collect();
+        writeln!(file, ",{}", names.join(","))?;
+
+        for (name, row) in names.iter().zip(&self.correlations) {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|r| r.map(|r| r.to_string()).unwrap_or_default())
+                .collect();
+            writeln!(file, "{name},{}", cells.join(","))?;
+        }
+
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if the file can't be written.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_heuristics::HeuristicRegistry;
+    use tetris_ml::sample_states;
+
+    use super::{ComponentStats, HeuristicsReport};
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_component_stats() {
+        let values: Vec<f64> = (0..=100).map(f64::from).collect();
+        let stats = ComponentStats::new("x".to_string(), &values, 4);
+
+        assert_close(stats.mean, 50.);
+        assert_close(stats.median, 50.);
+        assert_close(stats.p5, 5.);
+        assert_close(stats.p95, 95.);
+        assert_close(stats.max, 100.);
+        assert_eq!(stats.histogram, [25, 25, 25, 26]);
+    }
+
+    #[test]
+    fn test_correlations() {
+        let a = vec![1., 2., 3., 4.];
+        let b = vec![8., 6., 4., 2.];
+        let constant = vec![1.; 4];
+        let names = ["a", "b", "constant"].map(String::from).to_vec();
+
+        let report = HeuristicsReport::from_columns(names, &[a, b, constant], 10);
+
+        assert_close(report.correlations[0][0].unwrap(), 1.);
+        assert_close(report.correlations[0][1].unwrap(), -1.);
+        assert_eq!(report.correlations[2][0], None);
+        assert_eq!(report.components[2].histogram[0], 4);
+
+        let pairs = report.redundant_pairs(0.9);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].0, pairs[0].1), ("a", "b"));
+    }
+
+    #[test]
+    fn test_report_of_sampled_states() {
+        let heuristics = HeuristicRegistry::default()
+            .select(&["holes", "bertsekas_tsitsiklis"])
+            .unwrap();
+        let states = sample_states(1, 10, 0);
+
+        let report = HeuristicsReport::new(&heuristics, &states, 10);
+
+        assert_eq!(report.n_samples, states.len());
+        assert_eq!(report.components.len(), 23);
+        assert_eq!(report.components[0].name, "holes");
+        assert_eq!(report.components[22].name, "bertsekas_tsitsiklis.constant");
+        // Holes are counted by both heuristics.
+        assert_close(report.correlations[0][21].unwrap(), 1.);
+    }
+}
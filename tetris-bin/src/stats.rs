@@ -1,8 +1,10 @@
+use std::sync::Arc;
+
 use tetris_core::scoring::Score;
 use tetris_heuristics::used_heuristics::get_heuristics;
 
 use anyhow::Result;
-use tetris_ml::{Config, GA};
+use tetris_ml::{Agent, Config, Crossover, Mutation, Optimizer, SearchConfig, Selection, GA};
 
 fn main() -> Result<()> {
     let configs = [
@@ -13,14 +15,25 @@ fn main() -> Result<()> {
             max_populations: Some(30),
             max_non_progress_populations: None,
             heuristics_used: get_heuristics(),
+            normalization: Vec::new(),
+            search: SearchConfig::GREEDY,
+            mcts: None,
+            network: None,
+            optimizer: Optimizer::Genetic,
+            selection: Selection::Roulette,
+            elite: 0,
+            crossover: Crossover::Blend,
+            mutation: Mutation::Uniform,
+            normalize_weights: false,
         },
     ];
 
-    for (idx, mut config) in configs.into_iter().enumerate() {
+    for (idx, config) in configs.into_iter().enumerate() {
         println!("Training number {idx}");
         println!("best_weights, best_fitness, mean_fitness, dropped_pieces, fours");
 
-        let mut ga = GA::new(&mut config, |population| {
+        let template = Agent::new(Arc::new(config.heuristics_used.clone()));
+        let mut ga = GA::new(&config, &template, |population| {
             let best_entity = population.get_best_entity();
 
             let Score {
@@ -31,7 +44,7 @@ fn main() -> Result<()> {
 
             println!(
                 "{:?}, {:?}, {:?}, {}, {}",
-                best_entity.weights,
+                best_entity.evaluator.weights,
                 population.biggest_fitness(),
                 population.mean_fitness(),
                 dropped_pieces,
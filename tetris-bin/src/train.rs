@@ -1,5 +1,4 @@
-use std::fs::File;
-use std::io::Write as _;
+use std::sync::Arc;
 
 use tetris_bin::args::CliArgs;
 use tetris_core::scoring::Score;
@@ -9,28 +8,60 @@ use anyhow::Result;
 use clap::Parser;
 
 fn main() -> Result<()> {
-    let config: Config = CliArgs::parse().try_into()?;
+    let args = CliArgs::parse();
+    let output = args.output.clone();
+    let config: Config = args.try_into()?;
+    config.validate()?;
 
-    let best_entity = run_model(config)?;
-    let weights = &best_entity.weights;
-
-    let mut file = File::create("best_weight.txt")?;
-    file.write_all(format!("{weights:?}").as_bytes())?;
+    let heuristics = Arc::new(config.heuristics_used.clone());
+    let normalizer = Arc::new(config.fit_normalizer()?);
+    let model = if let Some(network) = &config.network {
+        let template =
+            MlpEvaluator::new(Arc::new(network.clone()), heuristics).with_normalizer(normalizer);
+        SavedModel::from_mlp(&run_model(&config, &template)?.evaluator)
+    } else {
+        let template = Agent::new(heuristics).with_normalizer(normalizer);
+        SavedModel::from_agent(&run_model(&config, &template)?.evaluator)
+    };
+    model.save(output)?;
 
     Ok(())
 }
 
-fn run_model(mut config: Config) -> Result<Agent> {
-    let mut ga = GA::new(&mut config, |population| {
+/// Evolved parameters shown after every generation.
+trait PrintParams {
+    fn print_params(&self);
+}
+
+impl PrintParams for Agent {
+    fn print_params(&self) {
+        println!("Weights:\t{:?}", self.named_weights());
+    }
+}
+
+impl PrintParams for MlpEvaluator {
+    fn print_params(&self) {
+        println!("Network parameters:\t{:?}", self.params);
+    }
+}
+
+fn run_model<E>(config: &Config, template: &E) -> Result<Entity<E>>
+where
+    E: Parameterized + PrintParams + Clone + Send + Sync,
+{
+    let mut ga = GA::new(config, template, |population| {
         let best_entity = population.get_best_entity();
         println!("Best entity this population:");
-        println!("Weights:\t{:?}", best_entity.weights);
+        best_entity.evaluator.print_params();
 
         println!("Max fitness:\t{:.2}", population.biggest_fitness());
         println!("Worst fitness:\t{:.2}", population.lowest_fitness());
 
         println!("Mean fitness:\t{:.2}", population.mean_fitness());
         println!("Median fitness:\t{:.2}", population.median_fitness());
+        if let Some(spread) = population.distribution_spread() {
+            println!("Weights spread:\t{spread:.4}");
+        }
 
         let Score {
             cleared_rows,
@@ -0,0 +1,49 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use tetris_core::prelude::*;
+
+const NO_FULL_LINES: &str = "
+    ....#.....
+    ##.###.###
+    #########.
+    .#########
+";
+
+const SINGLE_FULL_LINE: &str = "
+    ....#.....
+    ##.###.###
+    ##########
+    .#########
+";
+
+const FOUR_FULL_LINES: &str = "
+    ....#.....
+    ##########
+    ##########
+    ##########
+    ##########
+";
+
+fn delete_full_lines(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Board::delete_full_lines");
+
+    for (name, ascii) in [
+        ("no_full_lines", NO_FULL_LINES),
+        ("single_full_line", SINGLE_FULL_LINE),
+        ("four_full_lines", FOUR_FULL_LINES),
+    ] {
+        let board: Board = ascii.parse().unwrap();
+
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || board,
+                |mut board| board.delete_full_lines(black_box(vec![0, 1, 2, 3])),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, delete_full_lines);
+criterion_main!(benches);
@@ -1,14 +1,19 @@
-use std::fmt::Display;
+use alloc::{vec, vec::Vec};
+use core::fmt::Display;
+use core::str::FromStr;
 
 use itertools::Itertools;
 
 use crate::entities::{Coord, PieceType};
-use colored::customcolors::CustomColor;
-use colored::Colorize;
+#[cfg(feature = "std")]
+use colored::{customcolors::CustomColor, Colorize};
 
 const W: usize = 10;
 const H: usize = 24;
 
+/// Single horizontal line of the board, indexed by `x`.
+pub type Row = [Option<PieceType>; W];
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub struct Board {
     grid: [[Option<PieceType>; H]; W],
@@ -50,6 +55,53 @@ impl Board {
         })
     }
 
+    #[must_use]
+    fn row(&self, y: usize) -> Row {
+        let mut row = [None; W];
+        for (x, cell) in row.iter_mut().enumerate() {
+            *cell = self.grid[x][y];
+        }
+        row
+    }
+
+    fn set_row(&mut self, y: usize, row: Row) {
+        for (x, cell) in row.into_iter().enumerate() {
+            self.grid[x][y] = cell;
+        }
+    }
+
+    /// Copy every row starting at `y` up to the top of the board.
+    #[must_use]
+    pub(crate) fn rows_from(&self, y: usize) -> Vec<Row> {
+        (y..H).map(|y| self.row(y)).collect()
+    }
+
+    /// Overwrite the rows starting at `y` with `rows`, reverting [`Board::rows_from`].
+    pub(crate) fn set_rows_from(&mut self, y: usize, rows: &[Row]) {
+        for (offset, row) in rows.iter().enumerate() {
+            self.set_row(y + offset, *row);
+        }
+    }
+
+    /// Index of the lowest row that differs between the two boards,
+    /// or the number of rows if the boards are equal.
+    #[must_use]
+    pub(crate) fn lowest_differing_row(&self, other: &Self) -> usize {
+        (0..H).find(|&y| self.row(y) != other.row(y)).unwrap_or(H)
+    }
+
+    /// Filled cells as a bitmask of rows for every column, ignoring the types of blocks.
+    #[must_use]
+    pub fn occupancy(&self) -> [u32; W] {
+        self.grid.map(|column| {
+            column
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.is_some())
+                .fold(0, |mask, (y, _)| mask | 1 << y)
+        })
+    }
+
     pub fn delete_full_lines(&mut self, ys: Vec<i32>) -> usize {
         let mut lines_to_delete = vec![];
         for y in ys {
@@ -68,7 +120,7 @@ impl Board {
     }
 
     #[must_use]
-    fn is_whole_line_occupied(&self, y: i32) -> bool {
+    pub(crate) fn is_whole_line_occupied(&self, y: i32) -> bool {
         (0..W)
             .map(|x| self.get(Coord::new(x as i32, y)))
             .all(|cell| cell.is_some())
@@ -88,26 +140,98 @@ impl Board {
     }
 }
 
+/// Error returned when an ASCII description of a [`Board`] is malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseBoardError {
+    TooManyRows(usize),
+    TooWideRow { row: usize, width: usize },
+    UnknownCell(char),
+}
+
+impl Display for ParseBoardError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooManyRows(rows) => write!(f, "board has {rows} rows, at most {H} allowed"),
+            Self::TooWideRow { row, width } => {
+                write!(f, "row {row} has {width} cells, at most {W} allowed")
+            }
+            Self::UnknownCell(cell) => write!(f, "unknown cell character {cell:?}"),
+        }
+    }
+}
+
+impl core::error::Error for ParseBoardError {}
+
+/// Parse a board drawn in ASCII, one line per row with the last line being the bottom of the board.
+///
+/// `.` is an empty cell, piece letters (`I`, `O`, `T`, `S`, `Z`, `J`, `L`) are blocks of that type
+/// and `#` is a block of unspecified type. Blank lines and surrounding whitespace are ignored.
+impl FromStr for Board {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<&str> = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        if rows.len() > H {
+            return Err(ParseBoardError::TooManyRows(rows.len()));
+        }
+
+        let mut board = Self::new();
+        for (y, row) in rows.into_iter().rev().enumerate() {
+            let width = row.chars().count();
+            if width > W {
+                return Err(ParseBoardError::TooWideRow { row: y, width });
+            }
+
+            for (x, cell) in row.chars().enumerate() {
+                let block = match cell {
+                    '.' => None,
+                    'I' => Some(PieceType::I),
+                    'O' | '#' => Some(PieceType::O),
+                    'T' => Some(PieceType::T),
+                    'S' => Some(PieceType::S),
+                    'Z' => Some(PieceType::Z),
+                    'J' => Some(PieceType::J),
+                    'L' => Some(PieceType::L),
+                    other => return Err(ParseBoardError::UnknownCell(other)),
+                };
+                board.set(block, (x, y));
+            }
+        }
+
+        Ok(board)
+    }
+}
+
+/// Colored terminal representation of the visible part of the board.
+#[cfg(feature = "std")]
 impl Display for Board {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         for y in (0..20).rev() {
             write!(f, "[")?;
 
             for x in 0..10 {
                 let cell = self.get(Coord::new(x, y));
-                let cell_str = cell.map_or("   ".black(), |block| match block {
-                    PieceType::I => " I ".cyan(),
-                    PieceType::O => " O ".yellow(),
-                    PieceType::T => " T ".purple(),
-                    PieceType::S => " S ".green(),
-                    PieceType::Z => " Z ".red(),
-                    PieceType::J => " J ".blue(),
-                    PieceType::L => " L ".custom_color(CustomColor {
-                        r: 255,
-                        g: 165,
-                        b: 0,
-                    }),
-                });
+                let cell_str = cell.map_or_else(
+                    || "   ".black(),
+                    |block| match block {
+                        PieceType::I => " I ".cyan(),
+                        PieceType::O => " O ".yellow(),
+                        PieceType::T => " T ".purple(),
+                        PieceType::S => " S ".green(),
+                        PieceType::Z => " Z ".red(),
+                        PieceType::J => " J ".blue(),
+                        PieceType::L => " L ".custom_color(CustomColor {
+                            r: 255,
+                            g: 165,
+                            b: 0,
+                        }),
+                    },
+                );
 
                 write!(f, "{cell_str}")?;
             }
@@ -116,3 +240,96 @@ impl Display for Board {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{Board, ParseBoardError, H, W};
+    use crate::entities::{Coord, PieceType};
+
+    fn board_with_full_rows() -> impl Strategy<Value = Board> {
+        (
+            prop::collection::vec(prop::collection::vec(any::<bool>(), H), W),
+            prop::collection::vec(0..H, 0..6),
+        )
+            .prop_map(|(cells, full_rows)| {
+                let mut board = Board::new();
+                for (x, column) in cells.into_iter().enumerate() {
+                    for (y, occupied) in column.into_iter().enumerate() {
+                        if occupied || full_rows.contains(&y) {
+                            board.set(Some(PieceType::T), (x, y));
+                        }
+                    }
+                }
+                board
+            })
+    }
+
+    #[test]
+    fn test_parse_ascii_board() {
+        let board: Board = "
+            ..T.......
+            #.TT....IO
+        "
+        .parse()
+        .unwrap();
+
+        assert_eq!(board.get(Coord::new(0, 0)), Some(PieceType::O));
+        assert_eq!(board.get(Coord::new(1, 0)), None);
+        assert_eq!(board.get(Coord::new(2, 1)), Some(PieceType::T));
+        assert_eq!(board.get(Coord::new(8, 0)), Some(PieceType::I));
+        assert_eq!(board.iter_blocks().count(), 6);
+
+        assert_eq!(
+            "..x".parse::<Board>(),
+            Err(ParseBoardError::UnknownCell('x'))
+        );
+        assert_eq!(
+            "...........".parse::<Board>(),
+            Err(ParseBoardError::TooWideRow { row: 0, width: 11 })
+        );
+    }
+
+    #[test]
+    fn test_occupancy() {
+        let board: Board = "
+            #.#......#
+            #.##.....#
+            ##########
+        "
+        .parse()
+        .unwrap();
+
+        assert_eq!(board.occupancy()[..4], [0b111, 0b001, 0b111, 0b011]);
+    }
+
+    proptest! {
+        #[test]
+        fn delete_full_lines_never_leaves_full_row(board in board_with_full_rows()) {
+            let mut board = board;
+            let full_before = (0..H as i32).filter(|&y| board.is_whole_line_occupied(y)).count();
+            let blocks_before = board.iter_blocks().count();
+
+            let deleted = board.delete_full_lines((0..H as i32).collect());
+
+            prop_assert_eq!(deleted, full_before);
+            prop_assert_eq!(board.iter_blocks().count(), blocks_before - deleted * W);
+            prop_assert!((0..H as i32).all(|y| !board.is_whole_line_occupied(y)));
+        }
+
+        #[test]
+        fn delete_full_lines_keeps_order_of_remaining_rows(board in board_with_full_rows()) {
+            let mut cleared = board;
+            cleared.delete_full_lines((0..H as i32).collect());
+
+            let remaining_rows = (0..H)
+                .filter(|&y| !board.is_whole_line_occupied(y as i32))
+                .map(|y| board.row(y));
+
+            for (y, row) in remaining_rows.enumerate() {
+                prop_assert_eq!(cleared.row(y), row);
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use anyhow::Result;
+use core::num::TryFromIntError;
+use core::ops::{self, AddAssign, SubAssign};
 use num::Integer;
-use std::ops::{self, AddAssign, SubAssign};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Coord<I: Integer> {
@@ -21,7 +21,7 @@ impl<I: Integer> From<(I, I)> for Coord<I> {
 }
 
 impl TryFrom<Coord<i32>> for Coord<usize> {
-    type Error = anyhow::Error;
+    type Error = TryFromIntError;
 
     fn try_from(value: Coord<i32>) -> Result<Self, Self::Error> {
         let x = value.x.try_into()?;
@@ -77,6 +77,19 @@ pub enum PieceType {
     L,
 }
 
+impl PieceType {
+    /// Every piece type, in the order they are drawn by the randomizer.
+    pub const ALL: [Self; 7] = [
+        Self::I,
+        Self::O,
+        Self::T,
+        Self::S,
+        Self::Z,
+        Self::J,
+        Self::L,
+    ];
+}
+
 /// Respresents every direction the piece can be moved.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
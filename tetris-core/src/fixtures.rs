@@ -0,0 +1,36 @@
+//! Boards shared by the benchmarks of every crate, so that their timings can be compared.
+
+/// Named boards in the ASCII format parsed by [`crate::board::Board`], from the easiest one.
+pub const BOARDS: [(&str, &str); 3] = [
+    ("empty", ""),
+    (
+        "midgame",
+        "
+        ......#...
+        .#...###..
+        ###.####.#
+        ####.#####
+        ########.#
+        #.########
+        ",
+    ),
+    (
+        "tall_with_holes",
+        "
+        ....#.....
+        ...###....
+        #.####..#.
+        ###.###.##
+        #.####.###
+        ####.#####
+        ##.#####.#
+        #.#######.
+        ######.###
+        ##.#######
+        #######.##
+        .#########
+        ##.#######
+        #########.
+        ",
+    ),
+];
@@ -1,6 +1,9 @@
 use crate::board::Board;
 use crate::entities::{Collision, Coord, Direction, Rotation};
+use crate::history::Undo;
 use crate::piece::Piece;
+use crate::placement::Placement;
+use crate::randomizer::Randomizer;
 use crate::scoring::Score;
 use crate::srs::get_offset_table;
 
@@ -13,24 +16,44 @@ pub struct Game {
     pub width: i32,
     pub height: i32,
     pub score: Score,
+    pub randomizer: Randomizer,
+    /// Outcome of the most recent piece lock, `None` until the first piece is dropped.
+    pub last_placement: Option<Placement>,
+    /// Whether the last successful move of the current piece was a rotation,
+    /// which makes locking it a spin if it can't move left, right or up either.
+    pub rotated_last: bool,
 }
 
 impl Game {
+    /// Create a game with a randomly chosen seed.
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn new() -> Self {
+        Self::from_seed(rand::random())
+    }
+
+    /// Create a game whose sequence of pieces is fully determined by `seed`.
+    #[must_use]
+    pub fn from_seed(seed: u64) -> Self {
+        let mut randomizer = Randomizer::from_seed(seed);
+
         Self {
             board: Board::new(),
-            piece: rand::random(),
-            next_piece: rand::random(),
+            piece: randomizer.next_piece(),
+            next_piece: randomizer.next_piece(),
             width: 10,
             height: 20,
             score: Score::default(),
+            randomizer,
+            last_placement: None,
+            rotated_last: false,
         }
     }
 
     pub fn reload_piece(&mut self) {
+        self.rotated_last = false;
         self.piece = self.next_piece;
-        self.next_piece = rand::random();
+        self.next_piece = self.randomizer.next_piece();
     }
 
     /// Check if after the move in the specified direction there will
@@ -72,18 +95,21 @@ impl Game {
     pub fn go_left(&mut self) {
         if self.doesnt_collide(&self.piece, Direction::Left) {
             self.piece.anchor_point.x -= 1;
+            self.rotated_last = false;
         }
     }
 
     pub fn go_right(&mut self) {
         if self.doesnt_collide(&self.piece, Direction::Right) {
             self.piece.anchor_point.x += 1;
+            self.rotated_last = false;
         }
     }
 
     pub fn go_down(&mut self) {
         if self.doesnt_collide(&self.piece, Direction::Down) {
             self.piece.anchor_point.y -= 1;
+            self.rotated_last = false;
         } else {
             // ewentualne zbicie linii, podwyższenie punktów
             self.on_drop();
@@ -93,6 +119,7 @@ impl Game {
     pub fn hard_drop(&mut self) {
         while self.doesnt_collide(&self.piece, Direction::Down) {
             self.piece.anchor_point.y -= 1;
+            self.rotated_last = false;
         }
 
         self.on_drop();
@@ -118,6 +145,7 @@ impl Game {
             // jeżeli tetrimino nie koliduje z żadnymi blokami, to znaleziono alternatywną pozycję
             if self.doesnt_collide(&kicked_piece, Direction::None) {
                 self.piece = kicked_piece;
+                self.rotated_last = true;
                 return;
             }
         }
@@ -126,13 +154,86 @@ impl Game {
         self.piece = original_piece;
     }
 
+    /// Place `placement` as the current piece and hard drop it.
+    /// `rotated` tells whether the piece got to `placement` by a rotation,
+    /// which makes the lock a spin if the piece can't move left, right or up from there.
+    /// Returned [`Undo`] reverts the board, pieces and score with [`Game::unmake_move`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any block of `placement` is outside of the board. Placements overlapping
+    /// the stack are only caught in debug builds, use the ones found by move generation.
+    pub fn make_move(&mut self, placement: Piece, rotated: bool) -> Undo {
+        debug_assert!(
+            self.doesnt_collide(&placement, Direction::None),
+            "Placement overlaps the stack or the walls."
+        );
+
+        let mut landed = placement;
+        while self.doesnt_collide(&landed, Direction::Down) {
+            landed.anchor_point.y -= 1;
+        }
+
+        let lowest_row = landed.iter_blocks().map(|pos| pos.y).min().unwrap_or(0);
+        let undo = Undo::capture(self, lowest_row.max(0) as usize);
+
+        self.piece = landed;
+        self.rotated_last = rotated && landed == placement;
+        self.on_drop();
+
+        undo
+    }
+
+    /// Revert a move made with [`Game::make_move`] or [`Game::advance_to`].
+    /// Returned [`Undo`] makes the move again when passed back to this method.
+    pub fn unmake_move(&mut self, undo: Undo) -> Undo {
+        undo.restore(self)
+    }
+
+    /// Replace this game with `next` state, e.g. one found by an agent's search.
+    pub fn advance_to(&mut self, next: Self) -> Undo {
+        let lowest_row = self.board.lowest_differing_row(&next.board);
+        let undo = Undo::capture(self, lowest_row);
+
+        *self = next;
+
+        undo
+    }
+
+    /// Game is lost when blocks reach above the visible field,
+    /// or when the freshly spawned piece overlaps the stack.
     #[must_use]
     pub fn is_lost(&self) -> bool {
         (0..self.width).any(|idx| self.board.get((idx, self.height)).is_some())
+            || self
+                .piece
+                .iter_blocks()
+                .any(|pos| self.board.get(pos).is_some())
+    }
+
+    /// Piece is stuck in place: it can't move left, right or up.
+    fn is_immobile(&self, piece: &Piece) -> bool {
+        let mut raised = *piece;
+        raised.anchor_point.y += 1;
+        !self.doesnt_collide(piece, Direction::Left)
+            && !self.doesnt_collide(piece, Direction::Right)
+            && !self.doesnt_collide(&raised, Direction::None)
     }
 
     fn on_drop(&mut self) {
+        // Only checked after rotations, so most locks skip the collision checks.
+        let spin = self.rotated_last && self.is_immobile(&self.piece);
         self.set_piece_blocks_into_board();
+
+        let mut piece_cells_cleared = 0;
+        let mut cleared_row_mask = 0;
+        for pos in self.piece.iter_blocks() {
+            if self.board.is_whole_line_occupied(pos.y) {
+                piece_cells_cleared += 1;
+                cleared_row_mask |= 1 << pos.y;
+            }
+        }
+
         let n_cleans = self.board.delete_full_lines(
             self.piece
                 .iter_blocks()
@@ -140,6 +241,13 @@ impl Game {
                 .collect(),
         );
 
+        self.last_placement = Some(Placement {
+            piece: self.piece,
+            cleared_rows: n_cleans,
+            piece_cells_cleared,
+            cleared_row_mask,
+            spin,
+        });
         self.score.on_lines_clear(n_cleans);
         self.score.on_drop();
 
@@ -156,8 +264,195 @@ impl Game {
     }
 }
 
+#[cfg(feature = "std")]
 impl Default for Game {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::Game;
+    use crate::entities::{Coord, Direction, PieceType, Rotation};
+    use crate::piece::Piece;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Action {
+        Left,
+        Right,
+        Down,
+        HardDrop,
+        Rotate(Rotation),
+    }
+
+    fn action() -> impl Strategy<Value = Action> {
+        prop_oneof![
+            Just(Action::Left),
+            Just(Action::Right),
+            Just(Action::Down),
+            Just(Action::HardDrop),
+            Just(Action::Rotate(Rotation::Clockwise)),
+            Just(Action::Rotate(Rotation::Counterclockwise)),
+        ]
+    }
+
+    fn rotation() -> impl Strategy<Value = Rotation> {
+        prop_oneof![Just(Rotation::Clockwise), Just(Rotation::Counterclockwise)]
+    }
+
+    fn piece_type() -> impl Strategy<Value = PieceType> {
+        prop_oneof![
+            Just(PieceType::I),
+            Just(PieceType::O),
+            Just(PieceType::T),
+            Just(PieceType::S),
+            Just(PieceType::Z),
+            Just(PieceType::J),
+            Just(PieceType::L),
+        ]
+    }
+
+    const fn inverse(rotation: Rotation) -> Rotation {
+        match rotation {
+            Rotation::Clockwise => Rotation::Counterclockwise,
+            Rotation::Counterclockwise => Rotation::Clockwise,
+        }
+    }
+
+    fn apply(game: &mut Game, action: Action) {
+        match action {
+            Action::Left => game.go_left(),
+            Action::Right => game.go_right(),
+            Action::Down => game.go_down(),
+            Action::HardDrop => game.hard_drop(),
+            Action::Rotate(rotation) => game.rotate(rotation),
+        }
+    }
+
+    fn assert_invariants(game: &Game) {
+        for pos in game.piece.iter_blocks() {
+            assert!(
+                pos.x >= 0 && pos.x < game.width,
+                "piece outside of board: {pos:?}"
+            );
+            assert!(pos.y >= 0, "piece below the board: {pos:?}");
+            assert!(
+                game.board.get(pos).is_none(),
+                "piece overlaps board at {pos:?}"
+            );
+        }
+
+        for y in 0..game.height {
+            let is_full = (0..game.width).all(|x| game.board.get(Coord::new(x, y)).is_some());
+            assert!(!is_full, "row {y} is full after the line clear");
+        }
+
+        let score = game.score;
+        assert_eq!(
+            score.cleared_rows,
+            4 * score.fours + 3 * score.threes + 2 * score.twos + score.ones
+        );
+        assert_eq!(
+            score.score,
+            800 * score.fours + 500 * score.threes + 300 * score.twos + 100 * score.ones
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Placement overlaps")]
+    fn test_make_move_over_stack_is_caught() {
+        let mut game = Game::from_seed(0);
+        game.hard_drop();
+        let locked = game.last_placement.unwrap().piece;
+
+        game.make_move(locked, false);
+    }
+
+    proptest! {
+        #[test]
+        fn random_actions_keep_game_invariants(
+            seed in any::<u64>(),
+            actions in prop::collection::vec(action(), 0..400),
+        ) {
+            let mut game = Game::from_seed(seed);
+
+            for action in actions {
+                apply(&mut game, action);
+                if game.is_lost() {
+                    break;
+                }
+                assert_invariants(&game);
+            }
+        }
+
+        #[test]
+        fn same_seed_replays_same_game(
+            seed in any::<u64>(),
+            actions in prop::collection::vec(action(), 0..200),
+        ) {
+            let mut first = Game::from_seed(seed);
+            let mut second = Game::from_seed(seed);
+
+            for action in actions {
+                if first.is_lost() {
+                    break;
+                }
+                apply(&mut first, action);
+                apply(&mut second, action);
+            }
+
+            prop_assert_eq!(first, second);
+        }
+
+        #[test]
+        fn rotation_is_reverted_by_inverse_rotation_on_empty_board(
+            block_type in piece_type(),
+            x in 2..8_i32,
+            y in 3..18_i32,
+            initial_rotations in prop::collection::vec(rotation(), 0..4),
+            rotation in rotation(),
+        ) {
+            let mut game = Game::from_seed(0);
+            game.piece = Piece::new(block_type);
+            game.piece.anchor_point = Coord::new(x, y);
+            for initial_rotation in initial_rotations {
+                game.piece.rotate(initial_rotation);
+            }
+            let before = game.piece;
+
+            game.rotate(rotation);
+            game.rotate(inverse(rotation));
+
+            prop_assert_eq!(game.piece, before);
+        }
+
+        #[test]
+        fn unmake_move_restores_game(
+            seed in any::<u64>(),
+            actions in prop::collection::vec(action(), 0..200),
+            placement_x in 1..8_i32,
+        ) {
+            let mut game = Game::from_seed(seed);
+            for action in actions {
+                if game.is_lost() {
+                    break;
+                }
+                apply(&mut game, action);
+            }
+            prop_assume!(!game.is_lost());
+
+            let before = game.clone();
+            let mut placement = game.piece;
+            placement.anchor_point.x = placement_x;
+            prop_assume!(game.doesnt_collide(&placement, Direction::None));
+
+            let undo = game.make_move(placement, false);
+            game.unmake_move(undo);
+
+            prop_assert_eq!(game, before);
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::game::Game;
 #[derive(Debug, Default, Clone)]
 pub struct GameBuilder {
     board: Board,
+    seed: Option<u64>,
 }
 
 impl GameBuilder {
@@ -12,9 +13,16 @@ impl GameBuilder {
     pub const fn new() -> Self {
         Self {
             board: Board::new(),
+            seed: None,
         }
     }
 
+    #[must_use]
+    pub const fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
     #[must_use]
     pub fn add_piece(mut self, piece: PieceType, coord: Coord<usize>) -> Self {
         self.board.set(Some(piece), coord);
@@ -23,7 +31,10 @@ impl GameBuilder {
 
     #[must_use]
     pub fn build(self) -> Game {
-        let mut game = Game::new();
+        #[cfg(feature = "std")]
+        let mut game = self.seed.map_or_else(Game::new, Game::from_seed);
+        #[cfg(not(feature = "std"))]
+        let mut game = Game::from_seed(self.seed.unwrap_or_default());
         game.board = self.board;
 
         game
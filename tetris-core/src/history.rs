@@ -0,0 +1,231 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::board::Row;
+use crate::game::Game;
+use crate::piece::Piece;
+use crate::placement::Placement;
+use crate::randomizer::Randomizer;
+use crate::scoring::Score;
+
+/// Compact record of the game state before a move, used to revert it.
+///
+/// Only the rows that a move could have touched are stored: a drop never modifies
+/// anything below the lowest block of the placed piece.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Undo {
+    piece: Piece,
+    next_piece: Piece,
+    score: Score,
+    randomizer: Randomizer,
+    last_placement: Option<Placement>,
+    rotated_last: bool,
+    lowest_row: usize,
+    rows: Vec<Row>,
+}
+
+impl Undo {
+    #[must_use]
+    pub(crate) fn capture(game: &Game, lowest_row: usize) -> Self {
+        Self {
+            piece: game.piece,
+            next_piece: game.next_piece,
+            score: game.score,
+            randomizer: game.randomizer,
+            last_placement: game.last_placement,
+            rotated_last: game.rotated_last,
+            lowest_row,
+            rows: game.board.rows_from(lowest_row),
+        }
+    }
+
+    /// Restore the captured state into `game`, returning the record needed to redo the move.
+    pub(crate) fn restore(self, game: &mut Game) -> Self {
+        let redo = Self::capture(game, self.lowest_row);
+
+        game.piece = self.piece;
+        game.next_piece = self.next_piece;
+        game.score = self.score;
+        game.randomizer = self.randomizer;
+        game.last_placement = self.last_placement;
+        game.rotated_last = self.rotated_last;
+        game.board.set_rows_from(self.lowest_row, &self.rows);
+
+        redo
+    }
+}
+
+/// Undo/redo stack of moves made on a [`Game`].
+///
+/// When `capacity` is set, the oldest moves are forgotten once the limit is reached.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    capacity: Option<usize>,
+    undo_stack: VecDeque<Undo>,
+    redo_stack: Vec<Undo>,
+}
+
+impl History {
+    #[must_use]
+    pub const fn new(capacity: Option<usize>) -> Self {
+        Self {
+            capacity,
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Drop `placement` into the game like [`Game::make_move`] and remember how to revert it.
+    pub fn make_move(&mut self, game: &mut Game, placement: Piece, rotated: bool) {
+        let undo = game.make_move(placement, rotated);
+        self.push(undo);
+    }
+
+    /// Replace the game with `next` state and remember how to revert it.
+    pub fn advance_to(&mut self, game: &mut Game, next: Game) {
+        let undo = game.advance_to(next);
+        self.push(undo);
+    }
+
+    /// Record an already made move. Any previously undone moves can no longer be redone.
+    pub fn push(&mut self, undo: Undo) {
+        self.redo_stack.clear();
+        self.undo_stack.push_back(undo);
+
+        if matches!(self.capacity, Some(capacity) if self.undo_stack.len() > capacity) {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Revert the last move. Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self, game: &mut Game) -> bool {
+        let Some(undo) = self.undo_stack.pop_back() else {
+            return false;
+        };
+
+        self.redo_stack.push(game.unmake_move(undo));
+        true
+    }
+
+    /// Make the last undone move again. Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self, game: &mut Game) -> bool {
+        let Some(redo) = self.redo_stack.pop() else {
+            return false;
+        };
+
+        self.undo_stack.push_back(game.unmake_move(redo));
+        true
+    }
+
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    #[must_use]
+    pub const fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.undo_stack.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::History;
+    use crate::entities::{Coord, PieceType};
+    use crate::game::Game;
+    use crate::game_builder::GameBuilder;
+    use crate::piece::Piece;
+
+    fn dropped_i_piece(x: i32) -> Piece {
+        let mut piece = Piece::new(PieceType::I);
+        piece.anchor_point.x = x;
+        piece
+    }
+
+    #[test]
+    fn test_undo_redo_round_trip() {
+        let mut game = Game::from_seed(0);
+        let start = game.clone();
+        let mut history = History::new(None);
+
+        history.make_move(&mut game, dropped_i_piece(0), false);
+        history.make_move(&mut game, dropped_i_piece(3), false);
+        let after_moves = game.clone();
+
+        assert!(history.undo(&mut game));
+        assert!(history.undo(&mut game));
+        assert!(!history.undo(&mut game));
+        assert_eq!(game, start);
+
+        assert!(history.redo(&mut game));
+        assert!(history.redo(&mut game));
+        assert!(!history.redo(&mut game));
+        assert_eq!(game, after_moves);
+    }
+
+    #[test]
+    fn test_undo_restores_cleared_lines() {
+        let mut builder = GameBuilder::new();
+        for x in 1..10 {
+            builder = builder
+                .add_piece(PieceType::O, Coord::new(x, 0))
+                .add_piece(PieceType::O, Coord::new(x, 1));
+        }
+        let mut game = builder.build();
+        let start = game.clone();
+
+        let undo = game.make_move(dropped_i_piece(0), false);
+        assert_eq!(game.score.cleared_rows, 2);
+
+        let placement = game.last_placement.unwrap();
+        assert_eq!(placement.cleared_rows, 2);
+        assert_eq!(placement.piece_cells_cleared, 2);
+        assert_eq!(placement.piece.block_type, PieceType::I);
+
+        let redo = game.unmake_move(undo);
+        assert_eq!(game, start);
+
+        game.unmake_move(redo);
+        assert_eq!(game.score.cleared_rows, 2);
+        assert!(game.board.iter_blocks().all(|(coord, _)| coord.x == 0));
+    }
+
+    #[test]
+    fn test_bounded_history_forgets_oldest_moves() {
+        let mut game = Game::from_seed(0);
+        let mut history = History::new(Some(2));
+
+        for x in 0..5 {
+            history.make_move(&mut game, dropped_i_piece(x), false);
+        }
+
+        assert_eq!(history.len(), 2);
+        assert!(history.undo(&mut game));
+        assert!(history.undo(&mut game));
+        assert!(!history.undo(&mut game));
+        assert_eq!(game.score.dropped_pieces, 3);
+    }
+
+    #[test]
+    fn test_new_move_clears_redo() {
+        let mut game = Game::from_seed(0);
+        let mut history = History::new(None);
+
+        history.make_move(&mut game, dropped_i_piece(0), false);
+        history.undo(&mut game);
+        assert!(history.can_redo());
+
+        history.make_move(&mut game, dropped_i_piece(5), false);
+        assert!(!history.can_redo());
+    }
+}
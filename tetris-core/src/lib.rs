@@ -1,8 +1,17 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 pub mod board;
 pub mod entities;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 pub mod game;
 pub mod game_builder;
+pub mod history;
 pub mod piece;
+pub mod placement;
 pub mod prelude;
+pub mod randomizer;
 pub mod scoring;
 pub mod srs;
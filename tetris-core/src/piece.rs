@@ -13,16 +13,8 @@ pub struct Piece {
 
 impl Distribution<Piece> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Piece {
-        match rng.gen_range(0..7) {
-            0 => Piece::new(PieceType::I),
-            1 => Piece::new(PieceType::O),
-            2 => Piece::new(PieceType::T),
-            3 => Piece::new(PieceType::S),
-            4 => Piece::new(PieceType::Z),
-            5 => Piece::new(PieceType::J),
-            6 => Piece::new(PieceType::L),
-            _ => unreachable!(),
-        }
+        // Changing the sampled integer type would change the pieces of every seeded game.
+        Piece::new(PieceType::ALL[rng.gen_range(0..7_u32) as usize])
     }
 }
 
@@ -54,6 +46,7 @@ impl Piece {
         }
     }
 
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn random() -> Self {
         rand::random()
@@ -0,0 +1,16 @@
+use crate::piece::Piece;
+
+/// Outcome of locking a piece into the board.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Placement {
+    /// Piece in the position it was locked in, before any lines were cleared.
+    pub piece: Piece,
+    /// Number of lines cleared by locking the piece.
+    pub cleared_rows: usize,
+    /// How many of the piece's own blocks were removed by the cleared lines.
+    pub piece_cells_cleared: usize,
+    /// Bit `y` is set for every row `y` cleared by locking the piece.
+    pub cleared_row_mask: u32,
+    /// Piece was locked right after a rotation, unable to move left, right or up.
+    pub spin: bool,
+}
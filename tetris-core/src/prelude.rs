@@ -2,4 +2,7 @@ pub use crate::board::Board;
 pub use crate::entities::{Coord, PieceType, Rotation};
 pub use crate::game::Game;
 pub use crate::game_builder::GameBuilder;
+pub use crate::history::{History, Undo};
 pub use crate::piece::Piece;
+pub use crate::placement::Placement;
+pub use crate::randomizer::Randomizer;
@@ -0,0 +1,49 @@
+use rand::{Error, Rng, RngCore};
+
+use crate::piece::Piece;
+
+/// Deterministic source of pieces, so that a game can be replayed from its seed.
+///
+/// Implemented as the `SplitMix64` generator - its whole state is a single `u64`,
+/// so it can be cheaply copied together with every branched game state.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Randomizer {
+    state: u64,
+}
+
+impl Randomizer {
+    #[must_use]
+    pub const fn from_seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_piece(&mut self) -> Piece {
+        self.gen()
+    }
+}
+
+impl RngCore for Randomizer {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -10,7 +10,7 @@ pub struct Score {
 }
 
 impl Score {
-    pub fn on_lines_clear(&mut self, n_cleans: usize) {
+    pub const fn on_lines_clear(&mut self, n_cleans: usize) {
         self.cleared_rows += n_cleans;
         self.score += match n_cleans {
             4 => 800,
@@ -29,7 +29,7 @@ impl Score {
         }
     }
 
-    pub fn on_drop(&mut self) {
+    pub const fn on_drop(&mut self) {
         self.dropped_pieces += 1;
     }
 }
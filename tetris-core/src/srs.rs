@@ -1,3 +1,5 @@
+use alloc::{vec, vec::Vec};
+
 use crate::entities::{Coord, PieceType};
 
 #[must_use]
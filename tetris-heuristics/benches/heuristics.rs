@@ -0,0 +1,55 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tetris_core::fixtures::BOARDS;
+use tetris_core::prelude::*;
+use tetris_heuristics::used_heuristics::get_heuristics;
+use tetris_heuristics::{EvalContext, HeuristicRegistry};
+
+/// Every registered heuristic, after a T piece is dropped onto every board,
+/// so that placement heuristics have a placement to look at.
+fn heuristics(c: &mut Criterion) {
+    let games: Vec<(&str, Game)> = BOARDS
+        .iter()
+        .map(|&(board_name, ascii)| {
+            let mut game = Game::from_seed(0);
+            game.board = ascii.parse().unwrap();
+            game.piece = Piece::new(PieceType::T);
+            game.hard_drop();
+            (board_name, game)
+        })
+        .collect();
+
+    for heuristic in HeuristicRegistry::default().all() {
+        let mut group = c.benchmark_group(heuristic.name());
+        let mut components = vec![0.; heuristic.dimension()];
+
+        for (board_name, game) in &games {
+            let ctx = EvalContext::from_game(game);
+            group.bench_function(*board_name, |b| {
+                b.iter(|| heuristic.evaluate_into(black_box(&ctx), &mut components));
+            });
+        }
+
+        group.finish();
+    }
+}
+
+/// Cost of scoring a single candidate board with every default heuristic, as done by agents.
+fn default_heuristics(c: &mut Criterion) {
+    let heuristics = get_heuristics();
+    let mut group = c.benchmark_group("default_heuristics");
+
+    for (board_name, ascii) in BOARDS {
+        let board: Board = ascii.parse().unwrap();
+        group.bench_function(board_name, |b| {
+            b.iter(|| {
+                let ctx = EvalContext::from_board(black_box(&board));
+                heuristics.iter().map(|h| h.evaluate(&ctx)).sum::<f32>()
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, heuristics, default_heuristics);
+criterion_main!(benches);
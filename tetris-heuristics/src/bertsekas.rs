@@ -0,0 +1,128 @@
+//! Per-column feature vector from Bertsekas and Tsitsiklis, "Neuro-Dynamic Programming".
+
+use std::ops::RangeInclusive;
+
+use crate::features::{BoardFeatures, WIDTH};
+use crate::heuristic::{EvalContext, Heuristic, HeuristicScore};
+
+/// 10 column heights, 9 differences of adjacent heights, maximum height, holes and a constant.
+pub const N_FEATURES: usize = 2 * WIDTH + 2;
+
+const MAX_HEIGHT_IDX: usize = 2 * WIDTH - 1;
+const HOLES_IDX: usize = MAX_HEIGHT_IDX + 1;
+const CONSTANT_IDX: usize = HOLES_IDX + 1;
+
+pub const BERTSEKAS_TSITSIKLIS: BertsekasTsitsiklis = BertsekasTsitsiklis;
+
+/// Vector-valued heuristic which lets agents learn a separate weight for every column.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BertsekasTsitsiklis;
+
+impl Heuristic for BertsekasTsitsiklis {
+    fn name(&self) -> &'static str {
+        "bertsekas_tsitsiklis"
+    }
+
+    fn description(&self) -> &'static str {
+        "Column heights, their differences, maximum height, holes and a constant."
+    }
+
+    fn range(&self) -> RangeInclusive<HeuristicScore> {
+        0.0..=190.0
+    }
+
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        bertsekas_tsitsiklis_features(&ctx.features).iter().sum()
+    }
+
+    fn dimension(&self) -> usize {
+        N_FEATURES
+    }
+
+    fn evaluate_into(&self, ctx: &EvalContext, out: &mut [HeuristicScore]) {
+        out.copy_from_slice(&bertsekas_tsitsiklis_features(&ctx.features));
+    }
+
+    fn depends_only_on_board(&self) -> bool {
+        true
+    }
+
+    fn component_name(&self, idx: usize) -> String {
+        match idx {
+            0..WIDTH => format!("column_height_{idx}"),
+            WIDTH..MAX_HEIGHT_IDX => format!("height_diff_{}", idx - WIDTH),
+            MAX_HEIGHT_IDX => "max_height".to_string(),
+            HOLES_IDX => "holes".to_string(),
+            CONSTANT_IDX => "constant".to_string(),
+            _ => panic!("{} has only {N_FEATURES} components.", self.name()),
+        }
+    }
+}
+
+#[must_use]
+pub fn bertsekas_tsitsiklis_features(
+    board_features: &BoardFeatures,
+) -> [HeuristicScore; N_FEATURES] {
+    let heights = board_features.heights;
+    let mut features = [0.; N_FEATURES];
+
+    for (x, &height) in heights.iter().enumerate() {
+        features[x] = height as HeuristicScore;
+    }
+    for (x, pair) in heights.windows(2).enumerate() {
+        features[WIDTH + x] = pair[0].abs_diff(pair[1]) as HeuristicScore;
+    }
+
+    features[MAX_HEIGHT_IDX] = board_features.max_height() as HeuristicScore;
+    features[HOLES_IDX] = board_features.total_holes() as HeuristicScore;
+    features[CONSTANT_IDX] = 1.;
+
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+
+    use super::{bertsekas_tsitsiklis_features, BERTSEKAS_TSITSIKLIS, N_FEATURES};
+    use crate::features::BoardFeatures;
+    use crate::heuristic::{EvalContext, Heuristic};
+
+    #[test]
+    fn test_features_of_ascii_board() {
+        let board: Board = "
+            .#........
+            ##......#.
+            #.#.....##
+        "
+        .parse()
+        .unwrap();
+
+        let features = bertsekas_tsitsiklis_features(&BoardFeatures::new(&board));
+
+        let heights = [2., 3., 1., 0., 0., 0., 0., 0., 2., 1.];
+        let diffs = [1., 2., 1., 0., 0., 0., 0., 2., 1.];
+        assert_eq!(features[..10], heights);
+        assert_eq!(features[10..19], diffs);
+        assert_eq!(features[19..], [3., 1., 1.]);
+    }
+
+    #[test]
+    fn test_evaluate_into_matches_evaluate() {
+        let board: Board = "
+            ##..
+            #.##
+        "
+        .parse()
+        .unwrap();
+        let ctx = EvalContext::from_board(&board);
+
+        let mut components = [0.; N_FEATURES];
+        BERTSEKAS_TSITSIKLIS.evaluate_into(&ctx, &mut components);
+
+        let sum: f32 = components.iter().sum();
+        assert!((BERTSEKAS_TSITSIKLIS.evaluate(&ctx) - sum).abs() < f32::EPSILON);
+        assert_eq!(BERTSEKAS_TSITSIKLIS.component_name(3), "column_height_3");
+        assert_eq!(BERTSEKAS_TSITSIKLIS.component_name(21), "constant");
+    }
+}
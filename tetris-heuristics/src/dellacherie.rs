@@ -0,0 +1,280 @@
+//! Features used by Pierre Dellacherie's hand tuned agent and by El-Tetris.
+
+use tetris_core::prelude::*;
+
+use crate::features::{BoardFeatures, HEIGHT, WIDTH};
+use crate::heuristic::{BoardHeuristic, HeuristicScore, PlacementHeuristic};
+
+pub const LANDING_HEIGHT: PlacementHeuristic = PlacementHeuristic::new(
+    "landing_height",
+    "Height of the middle of the last placed piece.",
+    0.5..=20.0,
+    landing_height,
+);
+
+pub const ERODED_PIECE_CELLS: PlacementHeuristic = PlacementHeuristic::new(
+    "eroded_piece_cells",
+    "Lines cleared by the last piece times the number of its own cells removed.",
+    0.0..=16.0,
+    eroded_piece_cells,
+);
+
+pub const ROW_TRANSITIONS: BoardHeuristic = BoardHeuristic::new(
+    "row_transitions",
+    "Number of horizontal changes between filled and empty cells, walls count as filled.",
+    0.0..=220.0,
+    row_transitions,
+);
+
+pub const COLUMN_TRANSITIONS: BoardHeuristic = BoardHeuristic::new(
+    "column_transitions",
+    "Number of vertical changes between filled and empty cells, the floor counts as filled.",
+    0.0..=200.0,
+    column_transitions,
+);
+
+pub const CUMULATIVE_WELLS: BoardHeuristic = BoardHeuristic::new(
+    "cumulative_wells",
+    "Sum of 1 + 2 + ... + depth over every well.",
+    0.0..=1050.0,
+    cumulative_wells,
+);
+
+pub const HOLE_DEPTH: BoardHeuristic = BoardHeuristic::new(
+    "hole_depth",
+    "Sum of the distances of holes from the top of their columns.",
+    0.0..=1900.0,
+    hole_depth,
+);
+
+/// Mean row of the piece's blocks, counting the bottom row as spanning heights `0..1`.
+#[must_use]
+pub fn landing_height(placement: &Placement) -> HeuristicScore {
+    let (lowest, highest) = placement
+        .piece
+        .iter_blocks()
+        .fold((i32::MAX, i32::MIN), |(lo, hi), pos| {
+            (lo.min(pos.y), hi.max(pos.y))
+        });
+
+    (lowest + highest + 1) as HeuristicScore / 2.
+}
+
+/// Rewards clearing many lines with a large part of the piece itself.
+#[must_use]
+pub const fn eroded_piece_cells(placement: &Placement) -> HeuristicScore {
+    (placement.cleared_rows * placement.piece_cells_cleared) as HeuristicScore
+}
+
+#[must_use]
+pub fn row_transitions(features: &BoardFeatures) -> HeuristicScore {
+    let mut transitions = 0;
+
+    for (y, &filled) in features.row_fill.iter().enumerate() {
+        // Empty row only changes next to the walls.
+        if filled == 0 {
+            transitions += 2;
+            continue;
+        }
+
+        let y = y as i32;
+        transitions += (-1..WIDTH as i32)
+            .filter(|&x| features.is_filled(x, y) != features.is_filled(x + 1, y))
+            .count();
+    }
+
+    transitions as HeuristicScore
+}
+
+#[must_use]
+pub fn column_transitions(features: &BoardFeatures) -> HeuristicScore {
+    let mut transitions = 0;
+
+    for (x, &height) in features.heights.iter().enumerate() {
+        let (x, height) = (x as i32, height as i32);
+        transitions += (-1..height - 1)
+            .filter(|&y| features.is_filled(x, y) != features.is_filled(x, y + 1))
+            .count();
+
+        // Change from the highest block, or the floor, to empty cells above it.
+        if height < HEIGHT as i32 {
+            transitions += 1;
+        }
+    }
+
+    transitions as HeuristicScore
+}
+
+/// Well cells are empty cells with both neighbours filled.
+/// Each run of them in a column adds `1 + 2 + ... + depth`, so deep wells are penalized more.
+#[must_use]
+pub fn cumulative_wells(features: &BoardFeatures) -> HeuristicScore {
+    let mut score = 0;
+
+    // Above the highest block only walls are filled, so there are no well cells.
+    let max_height = features.max_height() as i32;
+
+    for x in 0..WIDTH as i32 {
+        let mut depth = 0;
+        for y in (0..max_height).rev() {
+            let is_well = !features.is_filled(x, y)
+                && features.is_filled(x - 1, y)
+                && features.is_filled(x + 1, y);
+
+            depth = if is_well { depth + 1 } else { 0 };
+            score += depth;
+        }
+    }
+
+    score as HeuristicScore
+}
+
+/// Like [`holes_present`](crate::heuristics::holes_present),
+/// but every hole counts as many times as it is deep below the top block of its column.
+#[must_use]
+pub fn hole_depth(features: &BoardFeatures) -> HeuristicScore {
+    let mut score = 0;
+
+    for (x, &height) in features.heights.iter().enumerate() {
+        if features.holes[x] == 0 {
+            continue;
+        }
+
+        let (x, top) = (x as i32, height as i32 - 1);
+        score += (0..top)
+            .filter(|&y| !features.is_filled(x, y))
+            .map(|y| top - y)
+            .sum::<i32>();
+    }
+
+    score as HeuristicScore
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+
+    use crate::features::BoardFeatures;
+
+    use super::{
+        column_transitions, cumulative_wells, eroded_piece_cells, hole_depth, landing_height,
+        row_transitions,
+    };
+
+    fn board(ascii: &str) -> Board {
+        ascii.parse().unwrap()
+    }
+
+    fn assert_score(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < f32::EPSILON,
+            "{actual} != {expected}"
+        );
+    }
+
+    fn drop_vertical_i(board: Board, x: i32) -> Placement {
+        let mut game = Game::from_seed(0);
+        game.board = board;
+
+        let mut piece = Piece::new(PieceType::I);
+        piece.anchor_point.x = x;
+        game.make_move(piece, false);
+
+        game.last_placement.unwrap()
+    }
+
+    #[test]
+    fn test_landing_height() {
+        let placement = drop_vertical_i(Board::new(), 0);
+        assert_score(landing_height(&placement), 2.);
+
+        let placement = drop_vertical_i(
+            board(
+                "
+                #.........
+                #.........
+                ",
+            ),
+            0,
+        );
+        assert_score(landing_height(&placement), 4.);
+    }
+
+    #[test]
+    fn test_eroded_piece_cells() {
+        let placement = drop_vertical_i(
+            board(
+                "
+                .#########
+                .#########
+                .#.#######
+                ",
+            ),
+            0,
+        );
+
+        assert_eq!(placement.cleared_rows, 2);
+        assert_score(eroded_piece_cells(&placement), 4.);
+    }
+
+    #[test]
+    fn test_row_transitions() {
+        // Every empty row is bordered by the walls.
+        assert_score(row_transitions(&BoardFeatures::new(&Board::new())), 40.);
+
+        let state = board(
+            "
+            #.#.......
+            ########..
+            ",
+        );
+        // 36 from the empty rows, 4 from the top row and 2 from the bottom row.
+        assert_score(row_transitions(&BoardFeatures::new(&state)), 42.);
+    }
+
+    #[test]
+    fn test_column_transitions() {
+        assert_score(column_transitions(&BoardFeatures::new(&Board::new())), 10.);
+
+        let state = board(
+            "
+            #.........
+            ..........
+            #.#.......
+            ",
+        );
+        // Column 0 changes 3 times, column 2 once and the 8 others only at the floor.
+        assert_score(column_transitions(&BoardFeatures::new(&state)), 12.);
+    }
+
+    #[test]
+    fn test_cumulative_wells() {
+        let state = board(
+            "
+            ..........
+            #.#.....#.
+            #.#.....#.
+            #.#.....#.
+            ",
+        );
+        // Column 1 and column 9 next to the wall are both wells of depth 3.
+        assert_score(
+            cumulative_wells(&BoardFeatures::new(&state)),
+            2. * (1. + 2. + 3.),
+        );
+    }
+
+    #[test]
+    fn test_hole_depth() {
+        let state = board(
+            "
+            #.........
+            .....#....
+            ..........
+            #....#....
+            ",
+        );
+        // Column 0: one hole at depth 1 and one at depth 2, column 5: one hole at depth 1.
+        assert_score(hole_depth(&BoardFeatures::new(&state)), 4.);
+    }
+}
@@ -0,0 +1,106 @@
+use tetris_core::prelude::*;
+
+/// Columns of the board.
+pub const WIDTH: usize = 10;
+/// Rows of the board that heuristics look at.
+pub const HEIGHT: usize = 20;
+
+/// Data about a board that many heuristics need, computed once per candidate board
+/// so that heuristics don't have to scan the board again.
+#[derive(Debug, Clone, Copy)]
+pub struct BoardFeatures<'a> {
+    pub board: &'a Board,
+    /// Number of rows up to and including the highest block of each column.
+    pub heights: [usize; WIDTH],
+    /// Empty cells below the highest block of each column.
+    pub holes: [usize; WIDTH],
+    /// Filled cells in each visible row, starting from the bottom.
+    pub row_fill: [usize; HEIGHT],
+    /// How much lower each column is than the lower of its neighbours, walls being infinitely high.
+    pub well_depths: [usize; WIDTH],
+}
+
+impl<'a> BoardFeatures<'a> {
+    #[must_use]
+    pub fn new(board: &'a Board) -> Self {
+        let mut heights = [0; WIDTH];
+        let mut holes = [0; WIDTH];
+        let mut row_fill = [0; HEIGHT];
+
+        for x in 0..WIDTH {
+            let mut filled = 0;
+            for (y, row_filled) in row_fill.iter_mut().enumerate() {
+                if board.get((x as i32, y as i32)).is_some() {
+                    heights[x] = y + 1;
+                    *row_filled += 1;
+                    filled += 1;
+                }
+            }
+            holes[x] = heights[x] - filled;
+        }
+
+        Self {
+            board,
+            heights,
+            holes,
+            row_fill,
+            well_depths: well_depths(&heights),
+        }
+    }
+
+    #[must_use]
+    pub fn max_height(&self) -> usize {
+        self.heights.iter().max().copied().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn total_holes(&self) -> usize {
+        self.holes.iter().sum()
+    }
+
+    /// Walls and the floor are treated as filled cells.
+    #[must_use]
+    pub fn is_filled(&self, x: i32, y: i32) -> bool {
+        !(0..WIDTH as i32).contains(&x) || y < 0 || self.board.get((x, y)).is_some()
+    }
+}
+
+/// How much lower each column is than the lower of its neighbours, walls being infinitely high.
+#[must_use]
+pub fn well_depths(heights: &[usize; WIDTH]) -> [usize; WIDTH] {
+    let mut well_depths = [0; WIDTH];
+    for (x, depth) in well_depths.iter_mut().enumerate() {
+        let left = if x == 0 { usize::MAX } else { heights[x - 1] };
+        let right = heights.get(x + 1).copied().unwrap_or(usize::MAX);
+
+        *depth = left.min(right).saturating_sub(heights[x]);
+    }
+    well_depths
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+
+    use super::BoardFeatures;
+
+    #[test]
+    fn test_features_of_ascii_board() {
+        let board: Board = "
+            .#........
+            ##......#.
+            #.#.....##
+        "
+        .parse()
+        .unwrap();
+
+        let features = BoardFeatures::new(&board);
+
+        assert_eq!(features.heights, [2, 3, 1, 0, 0, 0, 0, 0, 2, 1]);
+        assert_eq!(features.holes, [0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(features.row_fill[..4], [4, 3, 1, 0]);
+        assert_eq!(features.well_depths, [1, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(features.max_height(), 3);
+        assert_eq!(features.total_holes(), 1);
+    }
+}
@@ -0,0 +1,313 @@
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use tetris_core::prelude::*;
+use tetris_core::scoring::Score;
+
+use crate::features::BoardFeatures;
+
+pub type HeuristicScore = f32;
+pub type HeuristicRef = Arc<dyn Heuristic>;
+
+/// Everything a heuristic can look at when evaluating a candidate state.
+#[derive(Debug, Clone, Copy)]
+pub struct EvalContext<'a> {
+    /// Board to evaluate.
+    pub board: &'a Board,
+    /// Data derived from `board`, shared by all heuristics.
+    pub features: BoardFeatures<'a>,
+    /// Placement that produced `board`, if known.
+    pub placement: Option<Placement>,
+    /// Piece that will be placed next, if the board comes from a game.
+    pub piece: Option<Piece>,
+    /// Piece shown in the preview, if the board comes from a game.
+    pub next_piece: Option<Piece>,
+    pub score: Score,
+}
+
+impl<'a> EvalContext<'a> {
+    /// Context for a bare board, with no information about the game it comes from.
+    #[must_use]
+    pub fn from_board(board: &'a Board) -> Self {
+        Self {
+            board,
+            features: BoardFeatures::new(board),
+            placement: None,
+            piece: None,
+            next_piece: None,
+            score: Score::default(),
+        }
+    }
+
+    /// Context for a game state, usually one right after a piece was dropped.
+    #[must_use]
+    pub fn from_game(game: &'a Game) -> Self {
+        Self {
+            board: &game.board,
+            features: BoardFeatures::new(&game.board),
+            placement: game.last_placement,
+            piece: Some(game.piece),
+            next_piece: Some(game.next_piece),
+            score: game.score,
+        }
+    }
+}
+
+/// Single feature of a game state that agents weigh when choosing a move.
+pub trait Heuristic: Debug + Send + Sync {
+    /// Stable identifier, used to match heuristics with their weights.
+    fn name(&self) -> &'static str;
+
+    /// Short human readable explanation of what is measured.
+    fn description(&self) -> &'static str;
+
+    /// Values this heuristic is expected to return on a regular 10x20 board.
+    /// For vector-valued heuristics it's the range of a single component.
+    fn range(&self) -> RangeInclusive<HeuristicScore>;
+
+    /// Scalar value of the feature. For vector-valued heuristics, the sum of all components.
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore;
+
+    /// Number of components of the feature, each of them is weighed separately by agents.
+    fn dimension(&self) -> usize {
+        1
+    }
+
+    /// Write every component of the feature into `out`, which has [`Heuristic::dimension`] elements.
+    fn evaluate_into(&self, ctx: &EvalContext, out: &mut [HeuristicScore]) {
+        out[0] = self.evaluate(ctx);
+    }
+
+    /// Whether the value only depends on which cells of the board are filled,
+    /// so that it can be cached for boards reached by different moves.
+    fn depends_only_on_board(&self) -> bool {
+        false
+    }
+
+    /// Name of the `idx`-th component, the heuristic's name for scalar features.
+    fn component_name(&self, idx: usize) -> String {
+        if self.dimension() == 1 {
+            self.name().to_string()
+        } else {
+            format!("{}[{idx}]", self.name())
+        }
+    }
+}
+
+/// Total number of components, and therefore weights, of the given heuristics.
+#[must_use]
+pub fn total_dimension(heuristics: &[HeuristicRef]) -> usize {
+    heuristics.iter().map(|h| h.dimension()).sum()
+}
+
+/// Names of every component of the given heuristics, in the order of agent's weights.
+#[must_use]
+pub fn component_names(heuristics: &[HeuristicRef]) -> Vec<String> {
+    heuristics
+        .iter()
+        .flat_map(|h| (0..h.dimension()).map(|idx| h.component_name(idx)))
+        .collect()
+}
+
+/// Every component of every heuristic, in the order of agent's weights.
+#[must_use]
+pub fn evaluate_components(heuristics: &[HeuristicRef], ctx: &EvalContext) -> Vec<HeuristicScore> {
+    let mut values = vec![0.; total_dimension(heuristics)];
+    let mut rest = values.as_mut_slice();
+
+    for h in heuristics {
+        let (out, tail) = rest.split_at_mut(h.dimension());
+        h.evaluate_into(ctx, out);
+        rest = tail;
+    }
+
+    values
+}
+
+/// Heuristic which only needs the board and data derived from it, backed by a plain function.
+#[derive(Debug, Clone)]
+pub struct BoardHeuristic {
+    name: &'static str,
+    description: &'static str,
+    range: RangeInclusive<HeuristicScore>,
+    function: fn(&BoardFeatures) -> HeuristicScore,
+}
+
+impl BoardHeuristic {
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        description: &'static str,
+        range: RangeInclusive<HeuristicScore>,
+        function: fn(&BoardFeatures) -> HeuristicScore,
+    ) -> Self {
+        Self {
+            name,
+            description,
+            range,
+            function,
+        }
+    }
+}
+
+impl Heuristic for BoardHeuristic {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn range(&self) -> RangeInclusive<HeuristicScore> {
+        self.range.clone()
+    }
+
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        (self.function)(&ctx.features)
+    }
+
+    fn depends_only_on_board(&self) -> bool {
+        true
+    }
+}
+
+/// Vector-valued heuristic which only needs the board and data derived from it, backed by a plain function.
+#[derive(Debug, Clone)]
+pub struct VectorBoardHeuristic<const N: usize> {
+    name: &'static str,
+    description: &'static str,
+    range: RangeInclusive<HeuristicScore>,
+    function: fn(&BoardFeatures) -> [HeuristicScore; N],
+}
+
+impl<const N: usize> VectorBoardHeuristic<N> {
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        description: &'static str,
+        range: RangeInclusive<HeuristicScore>,
+        function: fn(&BoardFeatures) -> [HeuristicScore; N],
+    ) -> Self {
+        Self {
+            name,
+            description,
+            range,
+            function,
+        }
+    }
+}
+
+impl<const N: usize> Heuristic for VectorBoardHeuristic<N> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn range(&self) -> RangeInclusive<HeuristicScore> {
+        self.range.clone()
+    }
+
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        (self.function)(&ctx.features).iter().sum()
+    }
+
+    fn dimension(&self) -> usize {
+        N
+    }
+
+    fn evaluate_into(&self, ctx: &EvalContext, out: &mut [HeuristicScore]) {
+        out.copy_from_slice(&(self.function)(&ctx.features));
+    }
+
+    fn depends_only_on_board(&self) -> bool {
+        true
+    }
+}
+
+/// Heuristic describing the move itself rather than the resulting board, backed by a plain function.
+///
+/// Evaluates to `0` when the placement that produced the board is unknown.
+#[derive(Debug, Clone)]
+pub struct PlacementHeuristic {
+    name: &'static str,
+    description: &'static str,
+    range: RangeInclusive<HeuristicScore>,
+    function: fn(&Placement) -> HeuristicScore,
+}
+
+impl PlacementHeuristic {
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        description: &'static str,
+        range: RangeInclusive<HeuristicScore>,
+        function: fn(&Placement) -> HeuristicScore,
+    ) -> Self {
+        Self {
+            name,
+            description,
+            range,
+            function,
+        }
+    }
+}
+
+impl Heuristic for PlacementHeuristic {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn range(&self) -> RangeInclusive<HeuristicScore> {
+        self.range.clone()
+    }
+
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        ctx.placement.as_ref().map_or(0., self.function)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+
+    use super::{EvalContext, Heuristic};
+    use crate::heuristics::{holes_present, HOLES};
+
+    #[test]
+    fn test_board_heuristic_matches_function() {
+        let board: Board = "
+            #.#.
+            ....
+            ###.
+        "
+        .parse()
+        .unwrap();
+
+        let ctx = EvalContext::from_board(&board);
+        assert!((HOLES.evaluate(&ctx) - holes_present(&ctx.features)).abs() < f32::EPSILON);
+        assert_eq!(HOLES.name(), "holes");
+    }
+
+    #[test]
+    fn test_context_from_game_contains_last_placement() {
+        let mut game = GameBuilder::new().seed(3).build();
+        assert!(EvalContext::from_game(&game).placement.is_none());
+
+        let dropped = game.piece.block_type;
+        game.hard_drop();
+
+        let ctx = EvalContext::from_game(&game);
+        assert_eq!(ctx.placement.map(|p| p.piece.block_type), Some(dropped));
+        assert_eq!(ctx.piece, Some(game.piece));
+        assert_eq!(ctx.score.dropped_pieces, 1);
+    }
+}
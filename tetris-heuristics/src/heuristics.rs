@@ -1,56 +1,75 @@
 use itertools::Itertools;
-use tetris_core::prelude::*;
 
-pub type HeuristicScore = f32;
-pub type Heuristic = fn(&Board) -> HeuristicScore;
-
-/// Helper method to get height of each individual column in the tetris board.
+use crate::features::{BoardFeatures, HEIGHT, WIDTH};
+use crate::heuristic::{BoardHeuristic, HeuristicScore};
+
+pub const BUMPINESS: BoardHeuristic = BoardHeuristic::new(
+    "bumpiness",
+    "Sum of height differences between neighbouring columns.",
+    0.0..=180.0,
+    bumpyness,
+);
+
+pub const HOLES: BoardHeuristic = BoardHeuristic::new(
+    "holes",
+    "Number of empty cells with a block somewhere above them.",
+    0.0..=190.0,
+    holes_present,
+);
+
+pub const RELATIVE_DIFF: BoardHeuristic = BoardHeuristic::new(
+    "relative_diff",
+    "Difference between the highest and the lowest column.",
+    0.0..=20.0,
+    relative_diff,
+);
+
+pub const HIGHEST_BLOCK: BoardHeuristic = BoardHeuristic::new(
+    "highest_block",
+    "Height of the highest block on the board.",
+    1.0..=20.0,
+    highest_block,
+);
+
+pub const I_CLEAR_POTENTIAL: BoardHeuristic = BoardHeuristic::new(
+    "i_clear_potential",
+    "Most lines that a single vertical I piece could clear.",
+    0.0..=4.0,
+    i_clear_potential,
+);
+
+pub const DISTANCE_MEAN_FROM_4: BoardHeuristic = BoardHeuristic::new(
+    "distance_mean_from_4",
+    "Squared distance of the most common column height above 4.",
+    0.0..=256.0,
+    distance_mean_from_4,
+);
+
+/// Helper method to get the row of the highest block in each column, `0` for empty columns.
 #[must_use]
-fn get_cols_max_heights(state: &Board) -> [usize; 10] {
-    let mut highest_blocks_x_axis = [0; 10];
-
-    for (coord, _block) in state.iter_blocks() {
-        if coord.y > highest_blocks_x_axis[coord.x] {
-            highest_blocks_x_axis[coord.x] = coord.y;
-        }
-    }
-    highest_blocks_x_axis
+fn get_cols_max_heights(features: &BoardFeatures) -> [usize; 10] {
+    features.heights.map(|height| height.saturating_sub(1))
 }
 
 /// Measures the amount of holes present on board.
 /// Holes are defined as cells with no blocks that have some block above them.
 /// Distance to top block can be greater than one.
 #[must_use]
-pub fn holes_present(state: &Board) -> HeuristicScore {
-    let mut score = 0.;
-    let highest_blocks_x_axis = get_cols_max_heights(state);
-
-    for (x, highest_y) in highest_blocks_x_axis.into_iter().enumerate() {
-        score += (0..highest_y)
-            .rev()
-            .filter(|&y| state.get((x as i32, y as i32)).is_none())
-            .count() as f32;
-    }
-
-    score
+pub fn holes_present(features: &BoardFeatures) -> HeuristicScore {
+    features.total_holes() as HeuristicScore
 }
 
 /// Measures the height of the highest block in the entire tetris board.
 #[must_use]
-pub fn highest_block(state: &Board) -> HeuristicScore {
-    state
-        .iter_blocks()
-        .map(|(coord, _)| coord.y)
-        .max()
-        .unwrap_or(0) as f32
-        + 1.
+pub fn highest_block(features: &BoardFeatures) -> HeuristicScore {
+    features.max_height().max(1) as HeuristicScore
 }
 
 /// Measures the "bumpyness" of the columns in the grid.
 /// This means that difference in heights of each individual next and previous columns will be summed.
 #[must_use]
-pub fn bumpyness(state: &Board) -> HeuristicScore {
-    let highest_blocks_x_axis = get_cols_max_heights(state);
+pub fn bumpyness(features: &BoardFeatures) -> HeuristicScore {
+    let highest_blocks_x_axis = get_cols_max_heights(features);
 
     let mut score = 0.;
     for (prev, next) in highest_blocks_x_axis.into_iter().tuple_windows() {
@@ -62,40 +81,35 @@ pub fn bumpyness(state: &Board) -> HeuristicScore {
 
 /// Maximum minus minumum height of all the columns.
 #[must_use]
-pub fn relative_diff(state: &Board) -> HeuristicScore {
-    let heights = get_cols_max_heights(state);
+pub fn relative_diff(features: &BoardFeatures) -> HeuristicScore {
+    let heights = get_cols_max_heights(features);
     let max = heights.iter().max().copied().unwrap_or_default() as HeuristicScore;
     let min = heights.iter().min().copied().unwrap_or_default() as HeuristicScore;
     max - min
 }
 
+/// Vertical I piece dropped into a column covers the 4 rows above its top block,
+/// and clears every one of them that misses only that cell.
 #[must_use]
-pub fn i_clear_potential(state: &Board) -> HeuristicScore {
-    let mut game = Game::new();
-    game.board = *state;
-    game.piece = Piece::new(PieceType::I);
-    game.piece.anchor_point = Coord::new(0, game.piece.anchor_point.y);
-
-    let mut maximum_clears = 0;
-
-    for _ in 0..game.width {
-        let mut game_cpy = game.clone();
-        game_cpy.hard_drop();
-
-        maximum_clears = maximum_clears.max(game_cpy.score.cleared_rows - game.score.cleared_rows);
-        if maximum_clears == 4 {
-            break;
-        }
-
-        game.piece.anchor_point.x += 1;
-    }
+pub fn i_clear_potential(features: &BoardFeatures) -> HeuristicScore {
+    let maximum_clears = features
+        .heights
+        .iter()
+        .map(|&height| {
+            features.row_fill[height.min(HEIGHT)..(height + 4).min(HEIGHT)]
+                .iter()
+                .filter(|&&filled| filled == WIDTH - 1)
+                .count()
+        })
+        .max()
+        .unwrap_or_default();
 
     maximum_clears as HeuristicScore
 }
 
 #[must_use]
-pub fn distance_mean_from_4(state: &Board) -> HeuristicScore {
-    let heights = get_cols_max_heights(state);
+pub fn distance_mean_from_4(features: &BoardFeatures) -> HeuristicScore {
+    let heights = get_cols_max_heights(features);
 
     let most_common_height = heights
         .into_iter()
@@ -114,6 +128,8 @@ mod tests {
 
     use tetris_core::{entities::Coord, game_builder::GameBuilder};
 
+    use crate::features::BoardFeatures;
+
     use tetris_core::entities::PieceType as PT;
 
     use super::{
@@ -129,7 +145,7 @@ mod tests {
             .add_piece(PT::I, Coord::new(2, 5))
             .add_piece(PT::I, Coord::new(5, 9))
             .build();
-        let heights = get_cols_max_heights(&game.board);
+        let heights = get_cols_max_heights(&BoardFeatures::new(&game.board));
 
         assert_eq!(heights, [0, 1, 5, 0, 0, 9, 0, 0, 0, 0]);
     }
@@ -143,7 +159,7 @@ mod tests {
             .add_piece(PT::I, Coord::new(0, 3))
             .build();
 
-        let res = highest_block(&game.board);
+        let res = highest_block(&BoardFeatures::new(&game.board));
         assert!((res - 4.).abs() < f32::EPSILON);
     }
 
@@ -156,7 +172,7 @@ mod tests {
             .add_piece(PT::I, Coord::new(4, 4))
             .build();
 
-        let res = highest_block(&game.board);
+        let res = highest_block(&BoardFeatures::new(&game.board));
         assert!((res - 6.).abs() < f32::EPSILON);
     }
 
@@ -174,7 +190,7 @@ mod tests {
             .add_piece(PT::I, Coord::new(8, 2))
             .add_piece(PT::I, Coord::new(9, 2))
             .build();
-        let res = bumpyness(&game.board);
+        let res = bumpyness(&BoardFeatures::new(&game.board));
         assert!((res - 12.).abs() < f32::EPSILON);
     }
 
@@ -186,7 +202,7 @@ mod tests {
             .add_piece(PT::I, Coord::new(2, 7))
             .build();
 
-        let res = relative_diff(&game.board);
+        let res = relative_diff(&BoardFeatures::new(&game.board));
         assert!((res - 7.).abs() < f32::EPSILON);
     }
 
@@ -198,7 +214,7 @@ mod tests {
             .add_piece(PT::I, Coord::new(3, 3))
             .build();
 
-        let res = holes_present(&game.board);
+        let res = holes_present(&BoardFeatures::new(&game.board));
         assert!((res - 4.).abs() < f32::EPSILON);
     }
 
@@ -211,7 +227,7 @@ mod tests {
         }
         let game = gb.build();
 
-        let res = i_clear_potential(&game.board);
+        let res = i_clear_potential(&BoardFeatures::new(&game.board));
         assert!(((res - 1.).abs() < f32::EPSILON));
 
         // well on right
@@ -221,7 +237,7 @@ mod tests {
         }
         let game = gb.build();
 
-        let res = i_clear_potential(&game.board);
+        let res = i_clear_potential(&BoardFeatures::new(&game.board));
         assert!(((res - 1.).abs() < f32::EPSILON));
 
         // well on right height 2
@@ -232,7 +248,7 @@ mod tests {
         }
         let game = gb.build();
 
-        let res = i_clear_potential(&game.board);
+        let res = i_clear_potential(&BoardFeatures::new(&game.board));
         assert!(((res - 2.).abs() < f32::EPSILON));
     }
 }
@@ -1,5 +1,14 @@
+pub mod bertsekas;
+pub mod dellacherie;
+pub mod features;
+pub mod heuristic;
 pub mod heuristics;
+pub mod placement;
 pub mod prelude;
+pub mod presets;
+pub mod reachability;
+pub mod registry;
+pub mod structure;
 pub mod used_heuristics;
 
 pub use crate::prelude::*;
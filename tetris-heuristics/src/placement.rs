@@ -0,0 +1,209 @@
+//! Features of the move itself rather than of the board it leaves behind.
+
+use std::ops::RangeInclusive;
+
+use tetris_core::prelude::*;
+
+use crate::features::{well_depths, HEIGHT, WIDTH};
+use crate::heuristic::{EvalContext, Heuristic, HeuristicScore, PlacementHeuristic};
+
+pub const TETRIS: PlacementHeuristic = PlacementHeuristic::new(
+    "tetris",
+    "1 if the last piece cleared 4 lines at once, 0 otherwise.",
+    0.0..=1.0,
+    tetris,
+);
+
+pub const SPIN: PlacementHeuristic = PlacementHeuristic::new(
+    "spin",
+    "1 if the last piece was rotated into a spot it can't move out of, 0 otherwise.",
+    0.0..=1.0,
+    spin,
+);
+
+pub const FILLED_WELL: FilledWell = FilledWell;
+
+/// Needs the board the piece was locked into as well as the placement,
+/// so it can't be a plain [`PlacementHeuristic`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FilledWell;
+
+impl Heuristic for FilledWell {
+    fn name(&self) -> &'static str {
+        "filled_well"
+    }
+
+    fn description(&self) -> &'static str {
+        "1 if the last piece was put into the deepest well, 0 otherwise."
+    }
+
+    fn range(&self) -> RangeInclusive<HeuristicScore> {
+        0.0..=1.0
+    }
+
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        ctx.placement
+            .as_ref()
+            .map_or(0., |placement| filled_well(placement, ctx.board))
+    }
+}
+
+#[must_use]
+pub const fn tetris(placement: &Placement) -> HeuristicScore {
+    if placement.cleared_rows == 4 {
+        1.
+    } else {
+        0.
+    }
+}
+
+#[must_use]
+pub const fn spin(placement: &Placement) -> HeuristicScore {
+    if placement.spin {
+        1.
+    } else {
+        0.
+    }
+}
+
+/// Agents usually keep a well open for I pieces, this tells them when it gets plugged.
+/// `board` is the board after locking the piece, the well is looked for in the one before it.
+#[must_use]
+pub fn filled_well(placement: &Placement, board: &Board) -> HeuristicScore {
+    let columns = columns_before_lock(placement, board);
+    let visible = (1 << HEIGHT) - 1;
+    let heights = columns.map(|column| (u32::BITS - (column & visible).leading_zeros()) as usize);
+
+    let depths = well_depths(&heights);
+    let deepest = depths.iter().max().copied().unwrap_or_default();
+    let Some(well) = depths
+        .iter()
+        .position(|&depth| depth == deepest && depth > 0)
+    else {
+        return 0.;
+    };
+
+    if placement
+        .piece
+        .iter_blocks()
+        .any(|pos| pos.x == well as i32)
+    {
+        1.
+    } else {
+        0.
+    }
+}
+
+/// Occupancy of every column before `placement`: cleared rows are put back and the piece removed.
+fn columns_before_lock(placement: &Placement, board: &Board) -> [u32; WIDTH] {
+    let mut columns = board.occupancy().map(|column| {
+        let mut restored = 0;
+        let mut remaining = column;
+        for y in 0..u32::BITS {
+            if placement.cleared_row_mask & 1 << y != 0 {
+                restored |= 1 << y;
+            } else {
+                restored |= (remaining & 1) << y;
+                remaining >>= 1;
+            }
+        }
+        restored
+    });
+
+    for pos in placement.piece.iter_blocks() {
+        if let Some(column) = columns.get_mut(pos.x as usize) {
+            *column &= !(1 << pos.y);
+        }
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::entities::{Collision, Direction, Rotation};
+    use tetris_core::prelude::*;
+
+    use super::{filled_well, spin, tetris};
+
+    fn board(ascii: &str) -> Board {
+        ascii.parse().unwrap()
+    }
+
+    /// Drop `block_type` from a spot where its lowest, leftmost block is at `(x, y)`.
+    fn place_piece(board: Board, block_type: PieceType, x: i32, y: i32) -> (Placement, Game) {
+        let mut game = Game::from_seed(0);
+        game.board = board;
+
+        let mut piece = Piece::new(block_type);
+        let leftmost = piece.iter_blocks().map(|pos| pos.x).min().unwrap();
+        let lowest = piece.iter_blocks().map(|pos| pos.y).min().unwrap();
+        piece.anchor_point.x += x - leftmost;
+        piece.anchor_point.y += y - lowest;
+        game.make_move(piece, false);
+
+        (game.last_placement.unwrap(), game)
+    }
+
+    /// Game with a T piece lowered onto the floor of an empty board, but not locked yet.
+    fn landed_t() -> Game {
+        let mut game = Game::from_seed(0);
+        game.piece = Piece::new(PieceType::T);
+        while game.get_collision_after_move(&game.piece, Direction::Down) == Collision::None {
+            game.go_down();
+        }
+        game
+    }
+
+    #[test]
+    fn test_tetris_into_well() {
+        let state = board(
+            "
+            .#########
+            .#########
+            .#########
+            .#########
+            ",
+        );
+
+        let (placement, game) = place_piece(state, PieceType::I, 0, 10);
+        assert_eq!(placement.cleared_rows, 4);
+        assert!((tetris(&placement) - 1.).abs() < f32::EPSILON);
+        assert!((filled_well(&placement, &game.board) - 1.).abs() < f32::EPSILON);
+
+        let (placement, game) = place_piece(state, PieceType::I, 5, 10);
+        assert!(tetris(&placement).abs() < f32::EPSILON);
+        assert!(filled_well(&placement, &game.board).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_spin_only_when_stuck_after_rotation() {
+        // Rotating on the open floor leaves the piece free to move, so it isn't a spin.
+        let mut game = landed_t();
+        game.rotate(Rotation::Clockwise);
+        game.hard_drop();
+        assert!(spin(&game.last_placement.unwrap()).abs() < f32::EPSILON);
+
+        // T pointing down into a slot covered from the left.
+        let slot = board(
+            "
+            ####......
+            ###...####
+            ####.#####
+            ",
+        );
+        let mut piece = Piece::new(PieceType::T);
+        piece.rotate(Rotation::Clockwise);
+        piece.rotate(Rotation::Clockwise);
+        piece.anchor_point = Coord::new(4, 1);
+
+        for rotated in [true, false] {
+            let mut game = Game::from_seed(0);
+            game.board = slot;
+            game.make_move(piece, rotated);
+
+            let placement = game.last_placement.unwrap();
+            assert_eq!(placement.cleared_row_mask, 0b11);
+            assert_eq!(placement.spin, rotated);
+        }
+    }
+}
@@ -1,5 +1,28 @@
-pub use crate::heuristics::{Heuristic, HeuristicScore};
+pub use crate::heuristic::{
+    component_names, evaluate_components, total_dimension, BoardHeuristic, EvalContext, Heuristic,
+    HeuristicRef, HeuristicScore, PlacementHeuristic, VectorBoardHeuristic,
+};
+
+pub use crate::bertsekas::{bertsekas_tsitsiklis_features, BertsekasTsitsiklis};
+
+pub use crate::features::BoardFeatures;
+
+pub use crate::dellacherie::{
+    column_transitions, cumulative_wells, eroded_piece_cells, hole_depth, landing_height,
+    row_transitions,
+};
 
 pub use crate::heuristics::{
     bumpyness, distance_mean_from_4, highest_block, holes_present, i_clear_potential, relative_diff,
 };
+
+pub use crate::placement::{filled_well, spin, tetris, FilledWell};
+
+pub use crate::structure::{
+    covered_cells, deep_wells, deepest_well_column, deepest_well_depth, overhangs, row_fill_ratio,
+    true_holes,
+};
+
+pub use crate::reachability::{enclosed_holes, fillable_cells, reachable_cavities};
+
+pub use crate::registry::HeuristicRegistry;
@@ -0,0 +1,13 @@
+//! Published weights, to compare trained agents against.
+
+/// Weights found by Yiyuan Lee for El-Tetris.
+///
+/// Signs are flipped compared to the original, because agents choose the state with the lowest score.
+pub const EL_TETRIS: [(&str, f32); 6] = [
+    ("landing_height", 4.500_159),
+    ("eroded_piece_cells", -3.418_126_8),
+    ("row_transitions", 3.217_888_3),
+    ("column_transitions", 9.348_695),
+    ("holes", 7.899_265_5),
+    ("cumulative_wells", 3.385_597_2),
+];
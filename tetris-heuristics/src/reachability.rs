@@ -0,0 +1,183 @@
+//! Holes told apart by whether a piece can actually get into them.
+
+use std::collections::HashSet;
+
+use tetris_core::entities::{Collision, Direction};
+use tetris_core::prelude::*;
+
+use crate::features::{BoardFeatures, WIDTH};
+use crate::heuristic::{BoardHeuristic, HeuristicScore};
+
+pub const ENCLOSED_HOLES: BoardHeuristic = BoardHeuristic::new(
+    "enclosed_holes",
+    "Covered empty cells that no piece can be moved into.",
+    0.0..=190.0,
+    enclosed_holes,
+);
+
+pub const REACHABLE_CAVITIES: BoardHeuristic = BoardHeuristic::new(
+    "reachable_cavities",
+    "Covered empty cells that a piece can still be slid or rotated into.",
+    0.0..=190.0,
+    reachable_cavities,
+);
+
+/// Cells that a piece of some type can be locked into, as a bitmask of rows for every column.
+///
+/// Pieces are flood filled from their spawn position with the same moves a player has:
+/// shifting left and right, soft dropping and rotating with SRS wall kicks.
+#[must_use]
+pub fn fillable_cells(state: &Board) -> [u32; WIDTH] {
+    let mut game = Game::from_seed(0);
+    game.board = *state;
+
+    let mut fillable = [0; WIDTH];
+
+    for block_type in PieceType::ALL {
+        let spawn = Piece::new(block_type);
+        if game.get_collision_after_move(&spawn, Direction::None) != Collision::None {
+            continue;
+        }
+
+        let mut visited = HashSet::from([spawn]);
+        let mut stack = vec![spawn];
+
+        while let Some(piece) = stack.pop() {
+            let mut moved = Vec::with_capacity(5);
+
+            match game.get_collision_after_move(&piece, Direction::Down) {
+                Collision::None => {
+                    let mut lowered = piece;
+                    lowered.anchor_point.y -= 1;
+                    moved.push(lowered);
+                }
+                _ => {
+                    for pos in piece.iter_blocks() {
+                        fillable[pos.x as usize] |= 1 << pos.y;
+                    }
+                }
+            }
+
+            for action in [Game::go_left, Game::go_right] {
+                game.piece = piece;
+                action(&mut game);
+                moved.push(game.piece);
+            }
+            for rotation in [Rotation::Clockwise, Rotation::Counterclockwise] {
+                game.piece = piece;
+                game.rotate(rotation);
+                moved.push(game.piece);
+            }
+
+            for next in moved {
+                if visited.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+    }
+
+    fillable
+}
+
+/// Count covered empty cells, split into those which can and can't be filled.
+#[must_use]
+fn classify_covered_cells(features: &BoardFeatures) -> (usize, usize) {
+    if features.total_holes() == 0 {
+        return (0, 0);
+    }
+
+    let heights = features.heights;
+    let is_covered_hole =
+        |x: usize, y: usize| y < heights[x] && !features.is_filled(x as i32, y as i32);
+    let fillable = fillable_cells(features.board);
+
+    let mut reachable = 0;
+    let mut enclosed = 0;
+    for x in 0..WIDTH {
+        for y in (0..heights[x]).filter(|&y| is_covered_hole(x, y)) {
+            if fillable[x] & (1 << y) == 0 {
+                enclosed += 1;
+            } else {
+                reachable += 1;
+            }
+        }
+    }
+
+    (reachable, enclosed)
+}
+
+/// Unlike [`holes_present`](crate::heuristics::holes_present),
+/// ignores cavities which can still be filled by tucking a piece under an overhang.
+#[must_use]
+pub fn enclosed_holes(features: &BoardFeatures) -> HeuristicScore {
+    classify_covered_cells(features).1 as HeuristicScore
+}
+
+#[must_use]
+pub fn reachable_cavities(features: &BoardFeatures) -> HeuristicScore {
+    classify_covered_cells(features).0 as HeuristicScore
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+
+    use super::{enclosed_holes, fillable_cells, reachable_cavities};
+    use crate::features::BoardFeatures;
+    use crate::heuristics::holes_present;
+
+    fn assert_score(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < f32::EPSILON,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn test_tuck_under_overhang_is_not_a_hole() {
+        let board: Board = "
+            ###..##...
+            #.#.......
+            ###.......
+        "
+        .parse()
+        .unwrap();
+
+        // An O piece can slide left under columns 5 and 6, the cell in column 1 is walled in.
+        assert_score(reachable_cavities(&BoardFeatures::new(&board)), 4.);
+        assert_score(enclosed_holes(&BoardFeatures::new(&board)), 1.);
+        assert_score(holes_present(&BoardFeatures::new(&board)), 5.);
+    }
+
+    #[test]
+    fn test_t_slot_is_reachable_by_rotation() {
+        let board: Board = "
+            #..#######
+            #...######
+            ##.#######
+        "
+        .parse()
+        .unwrap();
+
+        // Cell under the overhang in column 3 is filled by dropping a T piece pointing left
+        // into the slot and rotating it to point down.
+        let fillable = fillable_cells(&board);
+        assert_ne!(fillable[3] & 0b010, 0);
+        assert_score(reachable_cavities(&BoardFeatures::new(&board)), 1.);
+        assert_score(enclosed_holes(&BoardFeatures::new(&board)), 0.);
+    }
+
+    #[test]
+    fn test_board_without_holes_is_not_searched() {
+        let board: Board = "
+            #.........
+            ##.......#
+        "
+        .parse()
+        .unwrap();
+
+        assert_score(enclosed_holes(&BoardFeatures::new(&board)), 0.);
+        assert_score(reachable_cavities(&BoardFeatures::new(&board)), 0.);
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+
+use crate::bertsekas::BERTSEKAS_TSITSIKLIS;
+use crate::dellacherie as d;
+use crate::heuristics as h;
+use crate::placement as p;
+use crate::reachability as r;
+use crate::structure as s;
+use crate::HeuristicRef;
+
+/// Collection of heuristics that can be looked up by their names.
+#[derive(Debug, Clone)]
+pub struct HeuristicRegistry {
+    heuristics: Vec<HeuristicRef>,
+}
+
+impl Default for HeuristicRegistry {
+    /// Registry with every heuristic implemented in this crate.
+    fn default() -> Self {
+        Self {
+            heuristics: vec![
+                Arc::new(h::BUMPINESS),
+                Arc::new(h::HOLES),
+                Arc::new(h::RELATIVE_DIFF),
+                Arc::new(h::HIGHEST_BLOCK),
+                Arc::new(h::I_CLEAR_POTENTIAL),
+                Arc::new(h::DISTANCE_MEAN_FROM_4),
+                Arc::new(d::LANDING_HEIGHT),
+                Arc::new(d::ERODED_PIECE_CELLS),
+                Arc::new(d::ROW_TRANSITIONS),
+                Arc::new(d::COLUMN_TRANSITIONS),
+                Arc::new(d::CUMULATIVE_WELLS),
+                Arc::new(d::HOLE_DEPTH),
+                Arc::new(BERTSEKAS_TSITSIKLIS),
+                Arc::new(s::DEEPEST_WELL_DEPTH),
+                Arc::new(s::DEEPEST_WELL_COLUMN),
+                Arc::new(s::DEEP_WELLS),
+                Arc::new(s::OVERHANGS),
+                Arc::new(s::TRUE_HOLES),
+                Arc::new(s::COVERED_CELLS),
+                Arc::new(s::ROW_FILL_RATIO),
+                Arc::new(r::ENCLOSED_HOLES),
+                Arc::new(r::REACHABLE_CAVITIES),
+                Arc::new(p::TETRIS),
+                Arc::new(p::SPIN),
+                Arc::new(p::FILLED_WELL),
+            ],
+        }
+    }
+}
+
+impl HeuristicRegistry {
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self {
+            heuristics: Vec::new(),
+        }
+    }
+
+    /// Add a heuristic to the registry.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a heuristic with the same name is already registered.
+    pub fn register(&mut self, heuristic: HeuristicRef) -> Result<()> {
+        if self.get(heuristic.name()).is_some() {
+            bail!("Heuristic {:?} is already registered.", heuristic.name());
+        }
+
+        self.heuristics.push(heuristic);
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<HeuristicRef> {
+        self.heuristics.iter().find(|h| h.name() == name).cloned()
+    }
+
+    /// Every registered heuristic, in registration order.
+    #[must_use]
+    pub fn all(&self) -> &[HeuristicRef] {
+        &self.heuristics
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.heuristics.iter().map(|h| h.name())
+    }
+
+    /// Look up heuristics by their names, keeping the order of `names`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the names is not registered or is repeated.
+    pub fn select(&self, names: &[impl AsRef<str>]) -> Result<Vec<HeuristicRef>> {
+        let mut selected: Vec<HeuristicRef> = Vec::with_capacity(names.len());
+
+        for name in names {
+            let name = name.as_ref();
+            let Some(heuristic) = self.get(name) else {
+                bail!(
+                    "Unknown heuristic {name:?}, available: {}.",
+                    self.names().collect::<Vec<_>>().join(", ")
+                );
+            };
+
+            if selected.iter().any(|h| h.name() == name) {
+                bail!("Heuristic {name:?} is selected more than once.");
+            }
+
+            selected.push(heuristic);
+        }
+
+        Ok(selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use super::HeuristicRegistry;
+    use crate::heuristics::HOLES;
+    use crate::presets::EL_TETRIS;
+
+    #[test]
+    fn test_registered_names_are_unique() {
+        let registry = HeuristicRegistry::default();
+        let names: HashSet<_> = registry.names().collect();
+
+        assert_eq!(names.len(), registry.all().len());
+    }
+
+    #[test]
+    fn test_select_keeps_requested_order() {
+        let registry = HeuristicRegistry::default();
+        let selected = registry.select(&["holes", "bumpiness"]).unwrap();
+
+        let names: Vec<_> = selected.iter().map(|h| h.name()).collect();
+        assert_eq!(names, ["holes", "bumpiness"]);
+    }
+
+    #[test]
+    fn test_select_rejects_unknown_and_repeated_names() {
+        let registry = HeuristicRegistry::default();
+
+        assert!(registry.select(&["holes", "no_such_heuristic"]).is_err());
+        assert!(registry.select(&["holes", "holes"]).is_err());
+    }
+
+    #[test]
+    fn test_presets_use_registered_heuristics() {
+        let registry = HeuristicRegistry::default();
+        let names: Vec<_> = EL_TETRIS.iter().map(|(name, _)| *name).collect();
+
+        assert!(registry.select(&names).is_ok());
+    }
+
+    #[test]
+    fn test_register_rejects_duplicates() {
+        let mut registry = HeuristicRegistry::empty();
+
+        assert!(registry.register(Arc::new(HOLES)).is_ok());
+        assert!(registry.register(Arc::new(HOLES)).is_err());
+    }
+}
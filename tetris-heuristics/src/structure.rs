@@ -0,0 +1,259 @@
+//! Heuristics describing the shape of the stack: wells, overhangs and how full the rows are.
+
+use crate::features::{BoardFeatures, HEIGHT, WIDTH};
+use crate::heuristic::{BoardHeuristic, HeuristicScore, VectorBoardHeuristic};
+
+pub const DEEPEST_WELL_DEPTH: BoardHeuristic = BoardHeuristic::new(
+    "deepest_well_depth",
+    "Depth of the deepest column compared to its lower neighbour.",
+    0.0..=20.0,
+    deepest_well_depth,
+);
+
+pub const DEEPEST_WELL_COLUMN: VectorBoardHeuristic<WIDTH> = VectorBoardHeuristic::new(
+    "deepest_well_column",
+    "One-hot encoded column of the deepest well, all zeros without any well.",
+    0.0..=1.0,
+    deepest_well_column,
+);
+
+pub const DEEP_WELLS: BoardHeuristic = BoardHeuristic::new(
+    "deep_wells",
+    "Number of wells deeper than 2 cells.",
+    0.0..=5.0,
+    deep_wells,
+);
+
+pub const OVERHANGS: BoardHeuristic = BoardHeuristic::new(
+    "overhangs",
+    "Covered empty cells which can still be filled by sliding a piece along their row.",
+    0.0..=190.0,
+    overhangs,
+);
+
+pub const TRUE_HOLES: BoardHeuristic = BoardHeuristic::new(
+    "true_holes",
+    "Covered empty cells closed off from the open part of their row.",
+    0.0..=190.0,
+    true_holes,
+);
+
+pub const COVERED_CELLS: BoardHeuristic = BoardHeuristic::new(
+    "covered_cells",
+    "Blocks above the lowest hole of each column.",
+    0.0..=190.0,
+    covered_cells,
+);
+
+pub const ROW_FILL_RATIO: VectorBoardHeuristic<HEIGHT> = VectorBoardHeuristic::new(
+    "row_fill_ratio",
+    "Fraction of filled cells in every visible row, starting from the bottom.",
+    0.0..=0.9,
+    row_fill_ratio,
+);
+
+#[must_use]
+pub fn deepest_well_depth(features: &BoardFeatures) -> HeuristicScore {
+    features.well_depths.into_iter().max().unwrap_or_default() as HeuristicScore
+}
+
+/// Leftmost column is chosen if several wells are equally deep.
+#[must_use]
+pub fn deepest_well_column(features: &BoardFeatures) -> [HeuristicScore; WIDTH] {
+    let depths = features.well_depths;
+    let mut one_hot = [0.; WIDTH];
+
+    let deepest = depths.iter().max().copied().unwrap_or_default();
+    if let Some(x) = depths
+        .iter()
+        .position(|&depth| depth == deepest && depth > 0)
+    {
+        one_hot[x] = 1.;
+    }
+
+    one_hot
+}
+
+#[must_use]
+pub fn deep_wells(features: &BoardFeatures) -> HeuristicScore {
+    features
+        .well_depths
+        .into_iter()
+        .filter(|&depth| depth > 2)
+        .count() as HeuristicScore
+}
+
+/// Count covered empty cells, split into those connected within their row to an uncovered
+/// empty cell, and those which aren't.
+#[must_use]
+fn covered_empty_cells(features: &BoardFeatures) -> (usize, usize) {
+    let heights = features.heights;
+    let is_empty = |x: usize, y: usize| !features.is_filled(x as i32, y as i32);
+
+    let mut overhangs = 0;
+    let mut true_holes = 0;
+
+    if features.total_holes() == 0 {
+        return (0, 0);
+    }
+
+    for y in 0..features.max_height() {
+        let mut x = 0;
+        while x < WIDTH {
+            if !is_empty(x, y) {
+                x += 1;
+                continue;
+            }
+
+            // Contiguous run of empty cells in this row.
+            let start = x;
+            while x < WIDTH && is_empty(x, y) {
+                x += 1;
+            }
+
+            let covered = (start..x).filter(|&cx| y < heights[cx]).count();
+            if covered < x - start {
+                overhangs += covered;
+            } else {
+                true_holes += covered;
+            }
+        }
+    }
+
+    (overhangs, true_holes)
+}
+
+#[must_use]
+pub fn overhangs(features: &BoardFeatures) -> HeuristicScore {
+    covered_empty_cells(features).0 as HeuristicScore
+}
+
+#[must_use]
+pub fn true_holes(features: &BoardFeatures) -> HeuristicScore {
+    covered_empty_cells(features).1 as HeuristicScore
+}
+
+/// Blocks which have to be cleared before every hole of their column is uncovered.
+#[must_use]
+pub fn covered_cells(features: &BoardFeatures) -> HeuristicScore {
+    let is_filled = |x: usize, y: usize| features.is_filled(x as i32, y as i32);
+
+    let mut score = 0;
+    for (x, &height) in features.heights.iter().enumerate() {
+        if features.holes[x] == 0 {
+            continue;
+        }
+        if let Some(lowest_hole) = (0..height).find(|&y| !is_filled(x, y)) {
+            score += (lowest_hole + 1..height)
+                .filter(|&y| is_filled(x, y))
+                .count();
+        }
+    }
+
+    score as HeuristicScore
+}
+
+#[must_use]
+pub fn row_fill_ratio(features: &BoardFeatures) -> [HeuristicScore; HEIGHT] {
+    features
+        .row_fill
+        .map(|count| count as HeuristicScore / WIDTH as HeuristicScore)
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+
+    use crate::features::BoardFeatures;
+
+    use super::{
+        covered_cells, deep_wells, deepest_well_column, deepest_well_depth, overhangs,
+        row_fill_ratio, true_holes,
+    };
+
+    fn board(ascii: &str) -> Board {
+        ascii.parse().unwrap()
+    }
+
+    fn features(board: &Board) -> BoardFeatures<'_> {
+        BoardFeatures::new(board)
+    }
+
+    fn assert_score(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < f32::EPSILON,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn test_wells() {
+        let state = board(
+            "
+            ##.##.###.
+            ##.######.
+            ##.######.
+            ##.######.
+            ",
+        );
+
+        // Column 2 and column 9 next to the wall are 4 deep, column 5 only 1.
+        assert_score(deepest_well_depth(&features(&state)), 4.);
+        assert_score(deep_wells(&features(&state)), 2.);
+
+        let one_hot = deepest_well_column(&features(&state));
+        assert_eq!(one_hot.iter().sum::<f32>() as usize, 1);
+        assert_score(one_hot[2], 1.);
+
+        assert!(deepest_well_column(&features(&Board::new()))
+            .iter()
+            .all(|&x| x == 0.));
+    }
+
+    #[test]
+    fn test_overhangs_and_true_holes() {
+        let state = board(
+            "
+            ###.......
+            ......#.#.
+            ####..##.#
+            ",
+        );
+
+        // Columns 0-2 are covered in the middle row, but open to the right through column 3.
+        assert_score(overhangs(&features(&state)), 3.);
+        // Column 8 at the bottom is closed off by columns 7 and 9.
+        assert_score(true_holes(&features(&state)), 1.);
+    }
+
+    #[test]
+    fn test_covered_cells() {
+        let state = board(
+            "
+            #....
+            #...#
+            ....#
+            #..#.
+            .#.#.
+            ",
+        );
+
+        // Column 0: 3 blocks above the hole at the bottom, column 4: 2 blocks above the holes.
+        assert_score(covered_cells(&features(&state)), 5.);
+    }
+
+    #[test]
+    fn test_row_fill_ratio() {
+        let state = board(
+            "
+            #.........
+            #####.....
+            ",
+        );
+
+        let ratios = row_fill_ratio(&features(&state));
+        assert_score(ratios[0], 0.5);
+        assert_score(ratios[1], 0.1);
+        assert!(ratios[2..].iter().all(|&ratio| ratio == 0.));
+    }
+}
@@ -1,15 +1,19 @@
-use crate::Heuristic;
+use crate::registry::HeuristicRegistry;
+use crate::HeuristicRef;
 
-#[must_use]
-pub fn get_heuristics() -> Vec<Heuristic> {
-    use crate::heuristics as h;
+/// Names of the heuristics used for training when none are selected explicitly.
+pub const DEFAULT_HEURISTICS: [&str; 6] = [
+    "bumpiness",
+    "holes",
+    "relative_diff",
+    "highest_block",
+    "i_clear_potential",
+    "distance_mean_from_4",
+];
 
-    vec![
-        h::bumpyness,
-        h::holes_present,
-        h::relative_diff,
-        h::highest_block,
-        h::i_clear_potential,
-        h::distance_mean_from_4,
-    ]
+#[must_use]
+pub fn get_heuristics() -> Vec<HeuristicRef> {
+    HeuristicRegistry::default()
+        .select(&DEFAULT_HEURISTICS)
+        .expect("Default heuristics are always registered.")
 }
@@ -0,0 +1,53 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use tetris_core::fixtures::BOARDS;
+use tetris_core::prelude::*;
+use tetris_ml::{Agent, SearchConfig};
+
+const SEED: u64 = 2024;
+const N_TURNS: usize = 100;
+
+fn possible_next_game_states(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Agent::get_all_possible_next_game_states");
+
+    for (name, ascii) in BOARDS {
+        let mut game = Game::from_seed(SEED);
+        game.board = ascii.parse().unwrap();
+        game.piece = Piece::new(PieceType::T);
+
+        group.bench_function(name, |b| {
+            b.iter(|| Agent::get_all_possible_next_game_states(black_box(&game)));
+        });
+    }
+
+    group.finish();
+}
+
+fn play_fixed_game(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Agent::play_for_n_turns_or_lose");
+    group.sample_size(10);
+
+    let mut agent = Agent::el_tetris();
+    agent.game = Game::from_seed(SEED);
+
+    for (name, search) in [
+        ("current", SearchConfig::GREEDY),
+        ("current_and_next", SearchConfig::with_depth(2)),
+        (
+            "expectimax_3x8",
+            SearchConfig::with_depth(3).with_beam_width(8).expectimax(),
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || agent.clone(),
+                |agent| agent.play_for_n_turns_or_lose(Some(N_TURNS), search),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, possible_next_game_states, play_fixed_game);
+criterion_main!(benches);
@@ -1,42 +1,51 @@
 use anyhow::{bail, Result};
 use rand::distributions::{Distribution, Uniform};
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashSet;
 use std::sync::Arc;
 
+use tetris_core::entities::{Collision, Direction};
 use tetris_core::prelude::*;
 use tetris_heuristics::prelude::*;
+use tetris_heuristics::presets;
 
-use crate::BranchingMode;
+use crate::search::{best_state, decide};
+use crate::{
+    Budget, Evaluator, Normalizer, Parameterized, SavedModel, SearchCache, SearchConfig,
+    SearchLimit,
+};
 
 #[derive(Debug, Clone)]
 pub struct Agent {
     pub game: Game,
     pub weights: Vec<f32>,
-    pub heuristics: Arc<Vec<Heuristic>>,
+    pub heuristics: Arc<Vec<HeuristicRef>>,
+    /// Rescaling of heuristic components applied before they are weighed.
+    pub normalizer: Arc<Normalizer>,
 }
 
 impl Agent {
     #[must_use]
-    pub fn new(heuristics: Arc<Vec<Heuristic>>) -> Self {
+    pub fn new(heuristics: Arc<Vec<HeuristicRef>>) -> Self {
         let rng = rand::thread_rng();
         let dist = Uniform::from(-1.0..1.0);
-        let n_weights = heuristics.len();
+        let n_weights = total_dimension(&heuristics);
         Self {
             game: Game::new(),
             weights: dist.sample_iter(rng).take(n_weights).collect(),
             heuristics,
+            normalizer: Arc::default(),
         }
     }
 
     /// # Errors
     ///
-    /// This function will return an error if weights length don't match the number of heuristics passed.
-    pub fn from_weights(weights: Vec<f32>, heuristics: &[Heuristic]) -> Result<Self> {
-        if weights.len() != heuristics.len() {
+    /// This function will return an error if weights length don't match the number of heuristics' components.
+    pub fn from_weights(weights: Vec<f32>, heuristics: &[HeuristicRef]) -> Result<Self> {
+        let n_weights = total_dimension(heuristics);
+        if weights.len() != n_weights {
             bail!(
-                "Weights size doesn't match: passed: {}, expected: {}",
+                "Weights size doesn't match: passed: {}, expected: {n_weights}",
                 weights.len(),
-                heuristics.len()
             );
         }
 
@@ -44,29 +53,59 @@ impl Agent {
             game: Game::new(),
             weights,
             heuristics: Arc::new(heuristics.to_vec()),
+            normalizer: Arc::default(),
         })
     }
 
+    #[must_use]
+    pub fn with_normalizer(self, normalizer: Arc<Normalizer>) -> Self {
+        Self { normalizer, ..self }
+    }
+
+    /// Agent evaluating states like this one, with other `weights` and a new game.
+    #[must_use]
+    pub fn offspring(&self, weights: Vec<f32>) -> Self {
+        Self {
+            game: Game::new(),
+            weights,
+            heuristics: Arc::clone(&self.heuristics),
+            normalizer: Arc::clone(&self.normalizer),
+        }
+    }
+
+    /// Agent with the El-Tetris features and weights, a published baseline to compare against.
+    #[must_use]
+    pub fn el_tetris() -> Self {
+        SavedModel::from_named_weights(&presets::EL_TETRIS)
+            .to_agent(&HeuristicRegistry::default())
+            .expect("El-Tetris heuristics are always registered.")
+    }
+
+    /// Weights paired with the names of heuristic components they belong to.
+    #[must_use]
+    pub fn named_weights(&self) -> Vec<(String, f32)> {
+        component_names(&self.heuristics)
+            .into_iter()
+            .zip(self.weights.iter().copied())
+            .collect()
+    }
+
     #[must_use]
     pub const fn fitness(&self) -> f64 {
         self.game.score.score as f64
     }
 
-    pub fn make_a_move(&mut self, branching_mode: BranchingMode) {
-        if let Some(next_state) = self.next_best_state(branching_mode) {
+    pub fn make_a_move(&mut self, search: SearchConfig) {
+        if let Some(next_state) = self.next_best_state(search) {
             self.game = next_state;
         }
     }
 
     #[must_use]
-    pub fn play_for_n_turns_or_lose(
-        self,
-        n_turns: Option<usize>,
-        branching_mode: BranchingMode,
-    ) -> Self {
+    pub fn play_for_n_turns_or_lose(self, n_turns: Option<usize>, search: SearchConfig) -> Self {
         let mut entity = self;
         for _ in 0..n_turns.unwrap_or(usize::MAX) {
-            entity.make_a_move(branching_mode);
+            entity.make_a_move(search);
             if entity.game.is_lost() {
                 break;
             }
@@ -75,112 +114,341 @@ impl Agent {
     }
 
     #[must_use]
-    pub fn play_until_lost(self, branching_mode: BranchingMode) -> Self {
-        self.play_for_n_turns_or_lose(None, branching_mode)
+    pub fn play_until_lost(self, search: SearchConfig) -> Self {
+        self.play_for_n_turns_or_lose(None, search)
     }
 
+    /// Best state after dropping the current piece, found with the given lookahead.
     #[must_use]
-    pub fn next_best_state(&self, branching_mode: BranchingMode) -> Option<Game> {
-        let next_state = match branching_mode {
-            BranchingMode::Current => Self::get_all_possible_next_game_states(self.game.clone())
-                .into_iter()
-                .min_by(|a, b| {
-                    self.forward_with_board(&a.board)
-                        .total_cmp(&self.forward_with_board(&b.board))
-                })?,
+    pub fn next_best_state(&self, search: SearchConfig) -> Option<Game> {
+        self.next_best_state_cached(search, &mut SearchCache::default())
+    }
 
-            BranchingMode::CurrentAndNext => {
-                Self::get_all_possible_next_game_states(self.game.clone())
-                    .into_iter()
-                    .map(|game| vec![game])
-                    .flat_map(|path: Vec<Game>| {
-                        Self::get_all_possible_next_game_states(path[0].clone())
-                            .into_iter()
-                            .map(move |next| vec![path[0].clone(), next])
-                    })
-                    .min_by(|path1, path2| {
-                        self.forward_with_board(&path1.last().unwrap().board)
-                            .total_cmp(&self.forward_with_board(&path2.last().unwrap().board))
-                    })?
-                    .first()?
-                    .clone()
-            }
-        };
+    /// Like [`Agent::next_best_state`], reusing evaluations from `cache` and adding new ones to it.
+    /// Cache has to be cleared whenever the weights change.
+    #[must_use]
+    pub fn next_best_state_cached(
+        &self,
+        search: SearchConfig,
+        cache: &mut SearchCache,
+    ) -> Option<Game> {
+        best_state(self, &self.game, search, cache, &SearchLimit::NONE)
+    }
 
-        Some(next_state)
+    /// Best state found within `budget` by iterative deepening: searches of increasing depth
+    /// up to `search.depth`, keeping the result of the deepest one that finished in time.
+    /// Greedy move is always found, even if the budget is already exhausted.
+    #[must_use]
+    pub fn decide(&self, search: SearchConfig, budget: Budget) -> Option<Game> {
+        self.decide_cached(search, budget, &mut SearchCache::default())
     }
 
-    const ACTIONS: [fn(&mut Game); 6] = [
-        Game::hard_drop,
-        Game::go_down,
-        Game::go_left,
-        Game::go_right,
-        |game| game.rotate(Rotation::Counterclockwise),
-        |game| game.rotate(Rotation::Clockwise),
-    ];
+    /// Like [`Agent::decide`], reusing evaluations from `cache` and adding new ones to it.
+    #[must_use]
+    pub fn decide_cached(
+        &self,
+        search: SearchConfig,
+        budget: Budget,
+        cache: &mut SearchCache,
+    ) -> Option<Game> {
+        decide(self, &self.game, search, budget, cache)
+    }
 
     /// Implementation of an algorithm to discover and collect all possible game states after 1 piece drop.
-    /// Check every option with DFS algorithm using branching by every possible move.
-    /// Next game states' boards are unique.
+    /// Next game states are unique, and are returned in the order of discovery,
+    /// so that agents break ties the same way for the same game.
+    /// States in which the game is lost are skipped.
+    #[must_use]
+    pub fn get_all_possible_next_game_states(game: &Game) -> Vec<Game> {
+        Self::get_all_possible_placements(game)
+            .into_iter()
+            .filter_map(|(placement, rotated)| {
+                let mut next_state = game.clone();
+                next_state.piece = placement;
+                next_state.rotated_last = rotated;
+                next_state.hard_drop();
+                (!next_state.is_lost()).then_some(next_state)
+            })
+            .collect()
+    }
+
+    /// Every position in which the current piece of `game` can land, to be dropped with
+    /// [`Game::make_move`], in the order of discovery. Some of them may lose the game.
+    /// Every position comes with whether a rotation can be the last move reaching it,
+    /// which makes locking it there a spin if the piece can't move left, right or up.
     ///
+    /// Check every option with DFS algorithm using branching by every possible move.
+    /// Only the piece is moved while branching, the rest of the game is never copied.
     /// Use hashset to delete pieces that were previously branched out to avoid repetition.
     #[must_use]
-    pub fn get_all_possible_next_game_states(mut game: Game) -> Vec<Game> {
-        let n_dropped_pieces = game.score.dropped_pieces;
+    pub fn get_all_possible_placements(game: &Game) -> Vec<(Piece, bool)> {
+        /// Piece reached by a sequence of moves, which either still falls or is locked.
+        /// Falling pieces know whether the last of the moves was a rotation.
+        enum Branch {
+            Falling(Piece, bool),
+            Landed(Piece),
+        }
+
+        if game.is_lost() {
+            return Vec::new();
+        }
 
-        lower_piece_before_branching(&mut game);
+        let mut scratch = game.clone();
+        lower_piece_before_branching(&mut scratch);
 
-        let mut games_stack = VecDeque::from([game]);
-        let mut next_states = HashSet::new();
+        let mut branches = vec![Branch::Falling(scratch.piece, false)];
+        let mut placements = Vec::new();
+        let mut placements_seen = HashSet::new();
         let mut piece_positions_visited: HashSet<Piece> = HashSet::new();
+        let mut reached_by_rotation = HashSet::new();
 
-        while let Some(popped_game) = games_stack.pop_front() {
-            if popped_game.is_lost() {
-                continue;
-            }
+        while let Some(branch) = branches.pop() {
+            let piece = match branch {
+                Branch::Landed(piece) => {
+                    if placements_seen.insert(piece) {
+                        placements.push(piece);
+                    }
+                    continue;
+                }
+                Branch::Falling(piece, rotated) => {
+                    if rotated {
+                        reached_by_rotation.insert(piece);
+                    }
+                    piece
+                }
+            };
 
-            if popped_game.score.dropped_pieces == n_dropped_pieces + 1 {
-                next_states.insert(popped_game);
+            if !piece_positions_visited.insert(piece) {
                 continue;
             }
 
-            if piece_positions_visited.contains(&popped_game.piece) {
-                continue;
+            let mut dropped = piece;
+            while scratch.get_collision_after_move(&dropped, Direction::Down) == Collision::None {
+                dropped.anchor_point.y -= 1;
             }
+            branches.push(Branch::Landed(dropped));
 
-            for action in Self::ACTIONS {
-                let mut branched_game = popped_game.clone();
-                action(&mut branched_game);
+            branches.push(if dropped == piece {
+                Branch::Landed(piece)
+            } else {
+                let mut lowered = piece;
+                lowered.anchor_point.y -= 1;
+                Branch::Falling(lowered, false)
+            });
 
-                games_stack.push_front(branched_game);
+            let moves: [fn(&mut Game); 4] = [
+                Game::go_left,
+                Game::go_right,
+                |game| game.rotate(Rotation::Counterclockwise),
+                |game| game.rotate(Rotation::Clockwise),
+            ];
+            for make_move in moves {
+                scratch.piece = piece;
+                scratch.rotated_last = false;
+                make_move(&mut scratch);
+                branches.push(Branch::Falling(scratch.piece, scratch.rotated_last));
             }
-
-            piece_positions_visited.insert(popped_game.piece);
         }
 
-        next_states.into_iter().collect()
+        placements
+            .into_iter()
+            .map(|piece| (piece, reached_by_rotation.contains(&piece)))
+            .collect()
     }
 
     #[must_use]
     pub fn forward(&self) -> HeuristicScore {
-        self.forward_with_board(&self.game.board)
+        self.forward_with_game(&self.game)
     }
 
     #[must_use]
     pub fn forward_with_board(&self, board: &Board) -> HeuristicScore {
-        self.weights
-            .iter()
-            .zip(self.heuristics.iter())
-            .map(|(weight, h)| h(board) * weight)
-            .sum()
+        self.forward_with_context(&EvalContext::from_board(board))
+    }
+
+    /// Evaluate a game state, letting heuristics see the placement that led to it.
+    #[must_use]
+    pub fn forward_with_game(&self, game: &Game) -> HeuristicScore {
+        self.forward_with_context(&EvalContext::from_game(game))
+    }
+
+    /// Like [`Agent::forward_with_game`], taking the part that only depends on the board
+    /// from `cache` when the same filled cells were evaluated before.
+    #[must_use]
+    pub fn forward_cached(&self, game: &Game, cache: &mut SearchCache) -> HeuristicScore {
+        let mut ctx = None;
+        let board_part = cache.evaluation(&game.board, || {
+            self.weighted_sum(ctx.insert(EvalContext::from_game(game)), true)
+        });
+
+        if self.heuristics.iter().all(|h| h.depends_only_on_board()) {
+            return board_part;
+        }
+        let ctx = ctx.unwrap_or_else(|| EvalContext::from_game(game));
+        board_part + self.weighted_sum(&ctx, false)
+    }
+
+    /// Weighted sum of every normalized heuristic component,
+    /// vector-valued heuristics use consecutive weights.
+    #[must_use]
+    pub fn forward_with_context(&self, ctx: &EvalContext) -> HeuristicScore {
+        self.weighted_sum(ctx, true) + self.weighted_sum(ctx, false)
+    }
+
+    /// Weighted sum of the components of heuristics which do, or don't, only depend on the board.
+    fn weighted_sum(&self, ctx: &EvalContext, board_only: bool) -> HeuristicScore {
+        let mut components = Vec::new();
+        let mut idx = 0;
+        let mut score = 0.;
+
+        for h in self.heuristics.iter() {
+            let h_weights = &self.weights[idx..idx + h.dimension()];
+
+            if h.depends_only_on_board() != board_only {
+                // Skipped, counted by the other sum.
+            } else if let [weight] = h_weights {
+                score += self.normalizer.apply(idx, h.evaluate(ctx)) * weight;
+            } else {
+                components.resize(h_weights.len(), 0.);
+                h.evaluate_into(ctx, &mut components);
+                score += h_weights
+                    .iter()
+                    .zip(&components)
+                    .enumerate()
+                    .map(|(offset, (weight, &component))| {
+                        self.normalizer.apply(idx + offset, component) * weight
+                    })
+                    .sum::<HeuristicScore>();
+            }
+
+            idx += h.dimension();
+        }
+
+        score
+    }
+}
+
+/// Linear model: weighted sum of normalized heuristic components.
+impl Evaluator for Agent {
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        self.forward_with_context(ctx)
+    }
+
+    fn evaluate_cached(&self, game: &Game, cache: &mut SearchCache) -> HeuristicScore {
+        self.forward_cached(game, cache)
+    }
+}
+
+/// Weights of the linear model are its parameters.
+impl Parameterized for Agent {
+    fn params(&self) -> &[f32] {
+        &self.weights
+    }
+
+    fn with_params(&self, params: Vec<f32>) -> Self {
+        self.offspring(params)
     }
 }
 
 fn lower_piece_before_branching(game: &mut Game) {
     let lowest_piece_block = game.piece.iter_blocks().map(|pos| pos.y).min().unwrap();
-    let highest_grid_block = highest_block(&game.board) as i32;
+    let highest_grid_block = highest_block(&BoardFeatures::new(&game.board)) as i32;
     let dist_to_lower = lowest_piece_block - highest_grid_block;
     for _ in 0..dist_to_lower {
-        game.go_down();
+        if game.get_collision_after_move(&game.piece, Direction::Down) != Collision::None {
+            break;
+        }
+        game.piece.anchor_point.y -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+    use tetris_heuristics::HeuristicRegistry;
+
+    use super::Agent;
+    use crate::test_utils::el_tetris_agent;
+    use crate::SearchConfig;
+
+    #[test]
+    fn test_vector_heuristic_gets_weight_per_component() {
+        let heuristics = HeuristicRegistry::default()
+            .select(&["holes", "bertsekas_tsitsiklis"])
+            .unwrap();
+        assert!(Agent::from_weights(vec![1.; 2], &heuristics).is_err());
+
+        // Only the weight of the last column height is set.
+        let mut weights = vec![0.; 23];
+        weights[10] = 1.;
+        let agent = Agent::from_weights(weights, &heuristics).unwrap();
+
+        let board: Board = "
+            .........#
+            #........#
+        "
+        .parse()
+        .unwrap();
+        assert!((agent.forward_with_board(&board) - 2.).abs() < f32::EPSILON);
+
+        let names: Vec<_> = agent
+            .named_weights()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names[..3], ["holes", "column_height_0", "column_height_1"]);
+        assert_eq!(names[22], "constant");
+    }
+
+    #[test]
+    fn test_placements_lead_to_next_states() {
+        let agent = el_tetris_agent(2, 10);
+
+        let mut game = agent.game.clone();
+        let mut made = Vec::new();
+        for (placement, rotated) in Agent::get_all_possible_placements(&game) {
+            let undo = game.make_move(placement, rotated);
+            if !game.is_lost() {
+                made.push(game.clone());
+            }
+            game.unmake_move(undo);
+        }
+
+        assert_eq!(game, agent.game);
+        assert_eq!(made, Agent::get_all_possible_next_game_states(&agent.game));
+    }
+
+    #[test]
+    fn test_t_spin_is_found_in_slot() {
+        let mut game = Game::from_seed(0);
+        game.board = "
+            ####......
+            ###...####
+            ####.#####
+        "
+        .parse()
+        .unwrap();
+        game.piece = Piece::new(PieceType::T);
+
+        let spins: Vec<Placement> = Agent::get_all_possible_next_game_states(&game)
+            .into_iter()
+            .filter_map(|state| state.last_placement)
+            .filter(|placement| placement.spin)
+            .collect();
+        // Rotating on the open right side isn't a spin, rotating into the slot is.
+        assert!(spins
+            .iter()
+            .all(|placement| placement.piece.anchor_point.x == 4));
+        assert!(spins
+            .iter()
+            .any(|placement| placement.cleared_row_mask == 0b11));
+    }
+
+    #[test]
+    fn test_el_tetris_clears_lines() {
+        let agent = el_tetris_agent(0, 0).play_for_n_turns_or_lose(Some(200), SearchConfig::GREEDY);
+
+        assert!(!agent.game.is_lost());
+        assert!(agent.game.score.cleared_rows >= 60);
     }
 }
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use crate::SearchCache;
+
+/// How long an agent may think about a single move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Budget {
+    /// The search always reaches its full depth.
+    #[default]
+    Unlimited,
+    /// Wall-clock time, counted from the moment the decision starts.
+    Time(Duration),
+    /// Number of boards the agent may evaluate, cached evaluations included.
+    Nodes(usize),
+}
+
+impl Budget {
+    /// Stop condition of a search which starts evaluating boards with `cache` now.
+    #[must_use]
+    pub fn start(self, cache: &SearchCache) -> SearchLimit {
+        match self {
+            Self::Unlimited => SearchLimit::NONE,
+            Self::Time(limit) => SearchLimit {
+                deadline: Instant::now().checked_add(limit),
+                max_evaluations: None,
+            },
+            Self::Nodes(nodes) => SearchLimit {
+                deadline: None,
+                max_evaluations: Some(cache.evaluation_stats.lookups.saturating_add(nodes)),
+            },
+        }
+    }
+}
+
+impl Display for Budget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unlimited => write!(f, "unlimited"),
+            Self::Time(limit) => write!(f, "{}ms", limit.as_millis()),
+            Self::Nodes(nodes) => write!(f, "{nodes} nodes"),
+        }
+    }
+}
+
+/// Point at which a running search gives up, see [`Budget::start`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchLimit {
+    deadline: Option<Instant>,
+    /// Total evaluation lookups of the search's cache.
+    max_evaluations: Option<usize>,
+}
+
+impl SearchLimit {
+    pub const NONE: Self = Self {
+        deadline: None,
+        max_evaluations: None,
+    };
+
+    #[must_use]
+    pub fn is_reached(&self, cache: &SearchCache) -> bool {
+        self.max_evaluations
+            .is_some_and(|max| cache.evaluation_stats.lookups > max)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tetris_core::prelude::*;
+
+    use super::Budget;
+    use crate::test_utils::el_tetris_agent;
+    use crate::{SearchCache, SearchConfig};
+
+    #[test]
+    fn test_node_limit() {
+        let mut cache = SearchCache::default();
+        let limit = Budget::Nodes(1).start(&cache);
+
+        cache.evaluation(&Board::new(), || 0.);
+        assert!(!limit.is_reached(&cache));
+        cache.evaluation(&Board::new(), || 0.);
+        assert!(limit.is_reached(&cache));
+        assert!(!Budget::Unlimited.start(&cache).is_reached(&cache));
+    }
+
+    #[test]
+    fn test_unlimited_decision_matches_full_search() {
+        let agent = el_tetris_agent(6, 10);
+        let search = SearchConfig::with_depth(2).with_beam_width(5);
+
+        assert_eq!(
+            agent.decide(search, Budget::Unlimited),
+            agent.next_best_state(search)
+        );
+    }
+
+    #[test]
+    fn test_exhausted_budget_falls_back_to_greedy_move() {
+        let agent = el_tetris_agent(8, 10);
+        let search = SearchConfig::with_depth(3).with_beam_width(5).expectimax();
+
+        // Greedy move is evaluated even without any budget, deeper searches aren't.
+        assert_eq!(
+            agent.decide(search, Budget::Nodes(0)),
+            agent.next_best_state(SearchConfig::GREEDY)
+        );
+
+        // Budget big enough for every depth but the last one.
+        let mut cache = SearchCache::default();
+        let shallow = SearchConfig { depth: 2, ..search };
+        let _ = agent.next_best_state_cached(shallow, &mut cache);
+        let shallower = agent.decide(search, Budget::Nodes(cache.evaluation_stats.lookups * 2));
+        assert_eq!(shallower, agent.next_best_state(shallow));
+    }
+
+    #[test]
+    fn test_time_budget_stops_deep_search() {
+        let agent = el_tetris_agent(9, 10);
+
+        // Time is up before any search deeper than the greedy one.
+        let decision = agent.decide(
+            SearchConfig::with_depth(5).peek(),
+            Budget::Time(Duration::ZERO),
+        );
+        assert_eq!(decision, agent.next_best_state(SearchConfig::GREEDY));
+    }
+}
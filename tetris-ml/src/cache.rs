@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use tetris_core::prelude::*;
+use tetris_heuristics::HeuristicScore;
+
+/// Evaluation cache entries kept by default before the cache is emptied.
+pub const DEFAULT_CACHE_CAPACITY: usize = 1 << 16;
+
+/// Filled cells of a board, the only thing most heuristics look at.
+pub type BoardKey = [u32; 10];
+
+/// How often a table already knew the answer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub lookups: usize,
+    pub hits: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that were hits, 0 before the first lookup.
+    #[must_use]
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups == 0 {
+            0.
+        } else {
+            self.hits as f64 / self.lookups as f64
+        }
+    }
+
+    const fn record(&mut self, hit: bool) {
+        self.lookups += 1;
+        if hit {
+            self.hits += 1;
+        }
+    }
+}
+
+/// Memory of agent's searches: board evaluations and statistics of transposition tables.
+///
+/// Cached values depend on the agent's weights, so a cache must not be shared between agents,
+/// nor kept after the weights change.
+#[derive(Debug, Clone)]
+pub struct SearchCache {
+    evaluations: HashMap<BoardKey, HeuristicScore>,
+    capacity: usize,
+    pub evaluation_stats: CacheStats,
+    pub transposition_stats: CacheStats,
+}
+
+impl Default for SearchCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl SearchCache {
+    /// Cache which forgets all evaluations once it holds `capacity` of them.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            evaluations: HashMap::new(),
+            capacity,
+            evaluation_stats: CacheStats::default(),
+            transposition_stats: CacheStats::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.evaluations.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.evaluations.is_empty()
+    }
+
+    /// Board-dependent part of the score of `board`, computed with `evaluate` if it's not cached.
+    pub fn evaluation(
+        &mut self,
+        board: &Board,
+        evaluate: impl FnOnce() -> HeuristicScore,
+    ) -> HeuristicScore {
+        let key = board.occupancy();
+        let cached = self.evaluations.get(&key).copied();
+        self.evaluation_stats.record(cached.is_some());
+
+        cached.unwrap_or_else(|| {
+            if self.evaluations.len() >= self.capacity {
+                self.evaluations.clear();
+            }
+
+            let value = evaluate();
+            self.evaluations.insert(key, value);
+            value
+        })
+    }
+
+    /// Count a lookup in a search's transposition table.
+    pub const fn record_transposition(&mut self, hit: bool) {
+        self.transposition_stats.record(hit);
+    }
+
+    /// Forget every evaluation, e.g. after agent's weights changed. Statistics are kept.
+    pub fn clear(&mut self) {
+        self.evaluations.clear();
+    }
+}
+
+/// Key of a search state: filled cells, the piece to drop and the number of pieces dropped so far.
+#[must_use]
+pub fn transposition_key(game: &Game, ply: usize) -> (BoardKey, PieceType, usize) {
+    (game.board.occupancy(), game.piece.block_type, ply)
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+
+    use super::SearchCache;
+
+    #[test]
+    fn test_evaluation_is_cached_until_capacity() {
+        let mut cache = SearchCache::with_capacity(2);
+        let empty = Board::new();
+        let one_block: Board = "#".parse().unwrap();
+        let two_blocks: Board = "##".parse().unwrap();
+
+        assert!((cache.evaluation(&empty, || 1.) - 1.).abs() < f32::EPSILON);
+        // Second lookup doesn't evaluate again.
+        assert!((cache.evaluation(&empty, || 5.) - 1.).abs() < f32::EPSILON);
+        cache.evaluation(&one_block, || 2.);
+        assert_eq!(cache.len(), 2);
+
+        // Full cache is emptied before a new board is stored.
+        cache.evaluation(&two_blocks, || 3.);
+        assert_eq!(cache.len(), 1);
+
+        assert_eq!(cache.evaluation_stats.lookups, 4);
+        assert_eq!(cache.evaluation_stats.hits, 1);
+        assert!((cache.evaluation_stats.hit_rate() - 0.25).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_boards_with_different_block_types_share_entry() {
+        let mut cache = SearchCache::default();
+        let t_block: Board = "T".parse().unwrap();
+        let i_block: Board = "I".parse().unwrap();
+
+        cache.evaluation(&t_block, || 1.);
+        assert!((cache.evaluation(&i_block, || 2.) - 1.).abs() < f32::EPSILON);
+    }
+}
@@ -1,56 +1,68 @@
-use crate::{population::Population, Agent, Config};
+use crate::population::{Entity, Population};
+use crate::{Config, Parameterized};
 use anyhow::Result;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-pub struct GA {
-    pub populations: Vec<Population>,
+/// Training loop of any [`crate::Optimizer`], the genetic algorithm by default.
+pub struct GA<E> {
+    /// Generations evaluated so far, with the games their entities played.
+    pub populations: Vec<Population<E>>,
     pub max_populations: Option<usize>,
     pub max_non_progress: Option<usize>,
+    /// Generation evaluated in the next step of training.
+    next_population: Population<E>,
 }
 
-impl GA {
-    /// Create new Genetic algorithm instance, containing all the populations.
+impl<E: Parameterized + Clone + Send + Sync> GA<E> {
+    /// Create new Genetic algorithm instance, containing all the populations,
+    /// which evolve the parameters of evaluators like `template`.
     /// # Errors
     ///
     /// This function will return an error if [`Config::validate`] fails.
-    pub fn new(config: &mut Config, population_evaluator: fn(&Population)) -> Result<Self> {
-        let start_population = Population::new(config, population_evaluator)?;
+    pub fn new(
+        config: &Config,
+        template: &E,
+        population_evaluator: fn(&Population<E>),
+    ) -> Result<Self> {
+        let start_population = Population::new(config, template, population_evaluator)?;
         Ok(Self {
             max_populations: config.max_populations,
             max_non_progress: config.max_non_progress_populations,
-            populations: vec![start_population],
+            populations: Vec::new(),
+            next_population: start_population,
         })
     }
 
-    #[must_use]
-    fn get_current_population(&self) -> &Population {
-        self.populations
-            .last()
-            .expect("New constructs GA with at least 1 starting population, so last population will always exist.")
-    }
-
     pub fn train(&mut self) {
         for _ in 0..self.max_populations.unwrap_or(usize::MAX) {
             if matches!(self.max_non_progress, Some(n) if n == 0) {
                 break;
             }
 
-            let current = self.get_current_population();
-            let next = current.advance_population();
+            let evaluated = self.next_population.evaluate();
 
-            let best_before = current.get_best_entity();
-            let best_after = next.get_best_entity();
+            if let Some(previous) = self.populations.last() {
+                let best_before = previous.get_best_entity();
+                let best_after = evaluated.get_best_entity();
 
-            if best_before.fitness() >= best_after.fitness() {
-                self.max_non_progress = self.max_non_progress.map(|x| x.saturating_sub(1));
+                if best_before.fitness() >= best_after.fitness() {
+                    self.max_non_progress = self.max_non_progress.map(|x| x.saturating_sub(1));
+                }
             }
 
-            self.populations.push(next);
+            self.next_population = evaluated.advance_population();
+            self.populations.push(evaluated);
         }
     }
 
+    /// Entity which scored the most in any evaluated generation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no generation has been evaluated, [`GA::train`] wasn't called
+    /// or there were no populations to train.
     #[must_use]
-    pub fn get_best_entity(&self) -> Agent {
+    pub fn get_best_entity(&self) -> Entity<E> {
         let population_with_best_agent = self
             .populations
             .par_iter()
@@ -59,7 +71,7 @@ impl GA {
                     .fitness()
                     .total_cmp(&p2.get_best_entity().fitness())
             })
-            .unwrap();
+            .expect("Training evaluates at least one population.");
 
         population_with_best_agent
             .entities
@@ -69,3 +81,56 @@ impl GA {
             .clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tetris_heuristics::prelude::*;
+
+    use super::GA;
+    use crate::test_utils::training_config;
+    use crate::{Agent, Config, Evaluator, Mutation, Optimizer, Parameterized};
+
+    /// El-Tetris agent ignoring its parameters, so that every entity clears rows.
+    #[derive(Debug, Clone)]
+    struct ElTetris(Agent, Vec<f32>);
+
+    impl Evaluator for ElTetris {
+        fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+            self.0.evaluate(ctx)
+        }
+    }
+
+    impl Parameterized for ElTetris {
+        fn params(&self) -> &[f32] {
+            &self.1
+        }
+
+        fn with_params(&self, params: Vec<f32>) -> Self {
+            Self(self.0.clone(), params)
+        }
+    }
+
+    #[test]
+    fn test_best_entity_was_evaluated() {
+        let genetic = Config {
+            max_drops: Some(30),
+            max_populations: Some(2),
+            ..training_config()
+        };
+        let cma_es = Config {
+            optimizer: Optimizer::CmaEs { sigma: 0.5 },
+            elite: 0,
+            mutation: Mutation::default(),
+            ..genetic.clone()
+        };
+        let template = ElTetris(Agent::el_tetris(), vec![0.; 2]);
+
+        for config in [genetic, cma_es] {
+            let mut ga = GA::new(&config, &template, |_| {}).unwrap();
+            ga.train();
+
+            assert_eq!(ga.populations.len(), config.max_populations.unwrap());
+            assert!(ga.get_best_entity().fitness() > 0.);
+        }
+    }
+}
@@ -1,8 +1,22 @@
 pub mod agent;
-pub mod branching_modes;
+pub mod budget;
+pub mod cache;
+#[cfg(feature = "training")]
 pub mod genetic_algorithm;
+pub mod mcts;
+pub mod mlp;
+pub mod model;
 pub mod model_config;
+pub mod normalization;
+pub mod operators;
+pub mod optimizer;
+pub mod policy;
+#[cfg(feature = "training")]
 pub mod population;
 pub mod prelude;
+pub mod search;
+pub mod selection;
+#[cfg(test)]
+mod test_utils;
 
 pub use crate::prelude::*;
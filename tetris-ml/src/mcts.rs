@@ -0,0 +1,451 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use tetris_core::prelude::*;
+use tetris_core::randomizer::Randomizer;
+
+use crate::search::{best_state, KNOWN_PIECES};
+use crate::{Agent, Evaluator, Policy, SearchCache, SearchConfig, SearchLimit};
+
+/// Budget and parameters of a single Monte Carlo tree search decision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    /// Number of simulations, the search stops earlier if `time_limit` runs out.
+    pub iterations: usize,
+    pub time_limit: Option<Duration>,
+    /// Greedy moves played with random pieces from a new leaf before it's evaluated.
+    pub rollout_depth: usize,
+    /// Weight of the exploration term of UCT, leaf values are rescaled to `0..=1`.
+    pub exploration: f64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 200,
+            time_limit: None,
+            rollout_depth: 2,
+            exploration: 1.,
+        }
+    }
+}
+
+/// Policy choosing moves with [`mcts_search`], `evaluator` playing the rollouts.
+#[derive(Debug, Clone)]
+pub struct MctsPolicy<E> {
+    pub evaluator: E,
+    pub config: MctsConfig,
+}
+
+impl<E> MctsPolicy<E> {
+    #[must_use]
+    pub const fn new(evaluator: E, config: MctsConfig) -> Self {
+        Self { evaluator, config }
+    }
+}
+
+impl<E: Evaluator> Policy for MctsPolicy<E> {
+    fn choose(&self, game: &Game) -> Option<Game> {
+        mcts_search(&self.evaluator, game, &self.config)
+    }
+}
+
+/// Best state after dropping the current piece of `game` found by Monte Carlo tree search,
+/// with `evaluator` playing the rollouts and evaluating leaves.
+#[must_use]
+pub fn mcts_search<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    game: &Game,
+    config: &MctsConfig,
+) -> Option<Game> {
+    let started = config.time_limit.map(|limit| (Instant::now(), limit));
+
+    // Seeded by what the player sees, so that the decision can't depend on the unseen pieces.
+    let mut hasher = DefaultHasher::new();
+    (&game.board, game.piece, game.next_piece).hash(&mut hasher);
+    let mut rng = Randomizer::from_seed(hasher.finish());
+
+    let mut tree = Tree::new(game.clone());
+    for _ in 0..config.iterations.max(1) {
+        if started.is_some_and(|(start, limit)| start.elapsed() >= limit) {
+            break;
+        }
+        tree.iterate(evaluator, config, &mut rng);
+    }
+
+    tree.best_move()
+}
+
+/// State in which the game's current piece is about to be dropped.
+struct Node {
+    game: Game,
+    visits: u32,
+    /// `None` until the node is reached for the first time.
+    children: Option<Vec<Child>>,
+}
+
+/// Placement of the node's piece, and statistics of the simulations that went through it.
+struct Child {
+    after: Game,
+    visits: u32,
+    /// Sum of the values of simulations which didn't lose.
+    value_sum: f64,
+    survived: u32,
+    /// Nodes for every piece type dealt after this placement that was tried.
+    next: Vec<(PieceType, usize)>,
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+    /// Lowest and highest leaf values, used to rescale them to `0..=1`.
+    bounds: Option<(f64, f64)>,
+    /// Evaluations shared by expansions and rollouts of all simulations.
+    cache: SearchCache,
+}
+
+impl Tree {
+    fn new(root: Game) -> Self {
+        Self {
+            nodes: vec![Node {
+                game: root,
+                visits: 0,
+                children: None,
+            }],
+            bounds: None,
+            cache: SearchCache::default(),
+        }
+    }
+
+    /// Select a path with UCT, expand its last node and evaluate a new placement with a rollout.
+    fn iterate<E: Evaluator + ?Sized>(
+        &mut self,
+        evaluator: &E,
+        config: &MctsConfig,
+        rng: &mut Randomizer,
+    ) {
+        let mut path = Vec::new();
+        let mut node = 0;
+
+        let value = loop {
+            if self.nodes[node].children.is_none() {
+                self.expand(node, evaluator);
+            }
+            let Some(child) = self.select(node, config.exploration) else {
+                // No placement is possible, the game is lost.
+                break None;
+            };
+            path.push((node, child));
+
+            let ply = path.len();
+            let placement = &self.nodes[node]
+                .children
+                .as_ref()
+                .expect("Node is expanded.")[child];
+            if placement.visits == 0 {
+                let after = placement.after.clone();
+                break rollout(
+                    evaluator,
+                    after,
+                    ply,
+                    config.rollout_depth,
+                    rng,
+                    &mut self.cache,
+                );
+            }
+
+            let block_type = if ply < KNOWN_PIECES {
+                placement.after.piece.block_type
+            } else {
+                *PieceType::ALL
+                    .choose(rng)
+                    .expect("There are 7 piece types.")
+            };
+            node = self.next_node(node, child, block_type, rng);
+        };
+
+        if let Some(value) = value {
+            self.bounds = Some(match self.bounds {
+                Some((lo, hi)) => (lo.min(value), hi.max(value)),
+                None => (value, value),
+            });
+        }
+
+        for (node, child) in path {
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+
+            let child = &mut node.children.as_mut().expect("Node is expanded.")[child];
+            child.visits += 1;
+            if let Some(value) = value {
+                child.value_sum += value;
+                child.survived += 1;
+            }
+        }
+    }
+
+    /// Create a child for every placement, the best ones by the agent's evaluation first,
+    /// so that they are tried first when the budget is small.
+    fn expand<E: Evaluator + ?Sized>(&mut self, node: usize, evaluator: &E) {
+        let next_states = Agent::get_all_possible_next_game_states(&self.nodes[node].game);
+        let mut placements: Vec<_> = next_states
+            .into_iter()
+            .map(|after| (evaluator.evaluate_cached(&after, &mut self.cache), after))
+            .collect();
+        placements.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let children = placements
+            .into_iter()
+            .map(|(_, after)| Child {
+                after,
+                visits: 0,
+                value_sum: 0.,
+                survived: 0,
+                next: Vec::new(),
+            })
+            .collect();
+        self.nodes[node].children = Some(children);
+    }
+
+    /// Unvisited placement if there is any, otherwise the one with the highest UCT score.
+    fn select(&self, node: usize, exploration: f64) -> Option<usize> {
+        let node = &self.nodes[node];
+        let children = node.children.as_ref()?;
+
+        if let Some(unvisited) = children.iter().position(|child| child.visits == 0) {
+            return Some(unvisited);
+        }
+
+        let ln_visits = f64::from(node.visits).ln();
+        let uct = |child: &Child| {
+            exploration.mul_add(
+                (ln_visits / f64::from(child.visits)).sqrt(),
+                self.mean_value(child),
+            )
+        };
+
+        (0..children.len()).max_by(|&a, &b| uct(&children[a]).total_cmp(&uct(&children[b])))
+    }
+
+    /// Mean value of the simulations through `child` rescaled to `0..=1`, losses counting as 0.
+    fn mean_value(&self, child: &Child) -> f64 {
+        if child.visits == 0 {
+            return 0.;
+        }
+
+        let visits = f64::from(child.visits);
+        let survived = f64::from(child.survived);
+        match self.bounds {
+            Some((lo, hi)) if hi > lo => {
+                survived.mul_add(-lo, child.value_sum) / ((hi - lo) * visits)
+            }
+            _ => survived / visits,
+        }
+    }
+
+    /// Node reached by dealing `block_type` after the `child` placement of `node`.
+    /// The piece in its preview isn't known yet, so it's drawn from `rng`.
+    fn next_node(
+        &mut self,
+        node: usize,
+        child: usize,
+        block_type: PieceType,
+        rng: &mut Randomizer,
+    ) -> usize {
+        let placement = &self.nodes[node]
+            .children
+            .as_ref()
+            .expect("Node is expanded.")[child];
+        if let Some(&(_, next)) = placement.next.iter().find(|(t, _)| *t == block_type) {
+            return next;
+        }
+
+        let mut game = placement.after.clone();
+        game.piece = Piece::new(block_type);
+        game.next_piece = rng.gen();
+        game.randomizer = Randomizer::from_seed(rng.gen());
+
+        let next = self.nodes.len();
+        self.nodes.push(Node {
+            game,
+            visits: 0,
+            children: None,
+        });
+        self.nodes[node]
+            .children
+            .as_mut()
+            .expect("Node is expanded.")[child]
+            .next
+            .push((block_type, next));
+        next
+    }
+
+    /// Root placement visited the most, better value breaking ties.
+    fn best_move(&self) -> Option<Game> {
+        self.nodes[0]
+            .children
+            .as_ref()?
+            .iter()
+            .max_by(|a, b| {
+                a.visits
+                    .cmp(&b.visits)
+                    .then(self.mean_value(a).total_cmp(&self.mean_value(b)))
+            })
+            .map(|child| child.after.clone())
+    }
+}
+
+/// Play `depth` greedy moves from `after`, reached after `ply` pieces, with random unseen pieces.
+/// The preview of `after` is never known, its current piece only right after the first ply.
+/// Value of the final board is its negated score, `None` if the game was lost.
+fn rollout<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    after: Game,
+    ply: usize,
+    depth: usize,
+    rng: &mut Randomizer,
+    cache: &mut SearchCache,
+) -> Option<f64> {
+    let mut game = after;
+    game.randomizer = Randomizer::from_seed(rng.gen());
+    game.next_piece = rng.gen();
+    if ply >= KNOWN_PIECES {
+        game.piece = rng.gen();
+    }
+
+    for _ in 0..depth {
+        game = best_state(
+            evaluator,
+            &game,
+            SearchConfig::GREEDY,
+            cache,
+            &SearchLimit::NONE,
+        )?;
+        if game.is_lost() {
+            return None;
+        }
+    }
+
+    Some(-f64::from(evaluator.evaluate_cached(&game, cache)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use tetris_core::randomizer::Randomizer;
+
+    use tetris_core::prelude::*;
+
+    use super::{Child, MctsConfig, MctsPolicy, Tree};
+    use crate::test_utils::el_tetris_agent;
+    use crate::{Agent, Policy};
+
+    fn mcts_policy(config: MctsConfig) -> MctsPolicy<Agent> {
+        MctsPolicy::new(Agent::el_tetris(), config)
+    }
+
+    #[test]
+    fn test_chooses_possible_next_state() {
+        let config = MctsConfig {
+            iterations: 50,
+            ..MctsConfig::default()
+        };
+        let game = Game::from_seed(0);
+
+        let next = mcts_policy(config).choose(&game).unwrap();
+        let possible = Agent::get_all_possible_next_game_states(&game);
+        assert!(possible.contains(&next));
+    }
+
+    /// Placement of the root visited `visits` times, `survived` of them with values summing
+    /// to `value_sum`.
+    fn child(visits: u32, survived: u32, value_sum: f64) -> Child {
+        Child {
+            after: Game::from_seed(0),
+            visits,
+            value_sum,
+            survived,
+            next: Vec::new(),
+        }
+    }
+
+    fn tree(children: Vec<Child>, bounds: Option<(f64, f64)>) -> Tree {
+        let mut tree = Tree::new(Game::from_seed(0));
+        tree.nodes[0].visits = children.iter().map(|child| child.visits).sum();
+        tree.nodes[0].children = Some(children);
+        tree.bounds = bounds;
+        tree
+    }
+
+    #[test]
+    fn test_values_are_rescaled_and_losses_count_as_worst() {
+        let tree = tree(Vec::new(), Some((-10., -2.)));
+
+        // Values -10 and -2 are the bounds, so they average to the middle.
+        assert!((tree.mean_value(&child(2, 2, -12.)) - 0.5).abs() < 1e-9);
+        // Loss counts as 0, like the worst value seen.
+        assert!((tree.mean_value(&child(2, 1, -2.)) - 0.5).abs() < 1e-9);
+        assert!(tree.mean_value(&child(1, 1, -10.)).abs() < 1e-9);
+
+        // Without a spread of values, only survival matters.
+        let flat = Tree {
+            bounds: Some((-3., -3.)),
+            ..tree
+        };
+        assert!((flat.mean_value(&child(4, 3, -9.)) - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_uct_tries_every_placement_then_balances_value_and_visits() {
+        let unvisited = tree(vec![child(5, 5, -10.), child(0, 0, 0.)], Some((-4., 0.)));
+        assert_eq!(unvisited.select(0, 1.), Some(1));
+
+        // First placement is better, but the second one has been tried much less.
+        let visited = tree(vec![child(50, 50, -50.), child(2, 2, -6.)], Some((-4., 0.)));
+        assert_eq!(visited.select(0, 0.), Some(0));
+        assert_eq!(visited.select(0, 2.), Some(1));
+    }
+
+    #[test]
+    fn test_most_visited_placement_is_played() {
+        let mut best_valued = child(3, 3, 0.);
+        best_valued.after = Game::from_seed(1);
+        let tree = tree(vec![child(10, 10, -30.), best_valued], Some((-4., 0.)));
+
+        assert_eq!(tree.best_move(), Some(Game::from_seed(0)));
+    }
+
+    #[test]
+    fn test_mcts_doesnt_peek_at_unseen_pieces() {
+        let config = MctsConfig {
+            iterations: 100,
+            ..MctsConfig::default()
+        };
+        let policy = mcts_policy(config);
+        let game = el_tetris_agent(3, 5).game;
+
+        // Rollouts deal random pieces, which mustn't be the ones this game would deal.
+        let mut other = game.clone();
+        other.randomizer = Randomizer::from_seed(1234);
+
+        let chosen = policy.choose(&game).unwrap();
+        let other_chosen = policy.choose(&other).unwrap();
+        assert_eq!(chosen.board, other_chosen.board);
+    }
+
+    #[test]
+    fn test_time_limit_stops_search() {
+        let config = MctsConfig {
+            iterations: usize::MAX,
+            time_limit: Some(Duration::from_millis(50)),
+            ..MctsConfig::default()
+        };
+
+        let start = Instant::now();
+        assert!(mcts_policy(config).choose(&Game::from_seed(2)).is_some());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
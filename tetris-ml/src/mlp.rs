@@ -0,0 +1,306 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tetris_core::prelude::*;
+use tetris_heuristics::features::{HEIGHT, WIDTH};
+use tetris_heuristics::prelude::*;
+
+use crate::{Evaluator, Normalizer, Parameterized, SearchCache};
+
+/// What a network is shown of the evaluated state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MlpInput {
+    /// Normalized components of the agent's heuristics.
+    #[default]
+    Heuristics,
+    /// Height of every column, as a fraction of the visible board's height.
+    ColumnHeights,
+}
+
+impl Display for MlpInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Heuristics => write!(f, "heuristics"),
+            Self::ColumnHeights => write!(f, "column-heights"),
+        }
+    }
+}
+
+impl FromStr for MlpInput {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "heuristics" => Ok(Self::Heuristics),
+            "column-heights" => Ok(Self::ColumnHeights),
+            _ => bail!("Unknown network input {s:?}, expected heuristics or column-heights."),
+        }
+    }
+}
+
+/// Architecture of a multilayer perceptron with `tanh` hidden layers and a linear output.
+///
+/// Parameters are kept outside, as a flat vector: for every layer the weights of each neuron
+/// followed by its bias, see [`MlpEvaluator`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MlpConfig {
+    pub input: MlpInput,
+    /// Number of neurons in every hidden layer, a single linear neuron is left without any.
+    pub hidden_layers: Vec<usize>,
+}
+
+impl MlpConfig {
+    /// Number of values the network reads for agents using `heuristics`.
+    #[must_use]
+    pub fn n_inputs(&self, heuristics: &[HeuristicRef]) -> usize {
+        match self.input {
+            MlpInput::Heuristics => total_dimension(heuristics),
+            MlpInput::ColumnHeights => WIDTH,
+        }
+    }
+
+    /// Length of the flat parameter vector for `n_inputs` inputs.
+    #[must_use]
+    pub fn n_params(&self, n_inputs: usize) -> usize {
+        self.layer_shapes(n_inputs)
+            .map(|(inputs, outputs)| (inputs + 1) * outputs)
+            .sum()
+    }
+
+    /// Whether the output only depends on the board, so that it can be cached by its cells.
+    #[must_use]
+    pub fn depends_only_on_board(&self, heuristics: &[HeuristicRef]) -> bool {
+        match self.input {
+            MlpInput::Heuristics => heuristics.iter().all(|h| h.depends_only_on_board()),
+            MlpInput::ColumnHeights => true,
+        }
+    }
+
+    /// Values the network reads for `ctx`.
+    #[must_use]
+    pub fn input(
+        &self,
+        heuristics: &[HeuristicRef],
+        normalizer: &Normalizer,
+        ctx: &EvalContext,
+    ) -> Vec<HeuristicScore> {
+        match self.input {
+            MlpInput::Heuristics => evaluate_components(heuristics, ctx)
+                .into_iter()
+                .enumerate()
+                .map(|(idx, value)| normalizer.apply(idx, value))
+                .collect(),
+            MlpInput::ColumnHeights => ctx
+                .features
+                .heights
+                .iter()
+                .map(|&height| height as HeuristicScore / HEIGHT as HeuristicScore)
+                .collect(),
+        }
+    }
+
+    /// Output of the network with `params` for `input`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `params` don't have [`MlpConfig::n_params`] values for the input's length.
+    #[must_use]
+    pub fn forward(&self, params: &[f32], input: &[HeuristicScore]) -> HeuristicScore {
+        assert_eq!(
+            params.len(),
+            self.n_params(input.len()),
+            "Wrong number of parameters."
+        );
+
+        let n_layers = self.hidden_layers.len() + 1;
+        let mut activations = input.to_vec();
+        let mut offset = 0;
+
+        for (layer, (inputs, outputs)) in self.layer_shapes(input.len()).enumerate() {
+            activations = params[offset..offset + (inputs + 1) * outputs]
+                .chunks_exact(inputs + 1)
+                .map(|neuron| {
+                    let (weights, bias) = neuron.split_at(inputs);
+                    let sum = weights
+                        .iter()
+                        .zip(&activations)
+                        .fold(bias[0], |sum, (weight, value)| weight.mul_add(*value, sum));
+                    if layer + 1 < n_layers {
+                        sum.tanh()
+                    } else {
+                        sum
+                    }
+                })
+                .collect();
+            offset += (inputs + 1) * outputs;
+        }
+
+        activations[0]
+    }
+
+    /// Number of inputs and outputs of every layer.
+    fn layer_shapes(&self, n_inputs: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let sizes = std::iter::once(n_inputs).chain(self.hidden_layers.iter().copied());
+        sizes.zip(self.hidden_layers.iter().copied().chain(std::iter::once(1)))
+    }
+}
+
+/// Network scoring states with its own parameters, evolved like the weights of an agent.
+#[derive(Debug, Clone)]
+pub struct MlpEvaluator {
+    pub config: Arc<MlpConfig>,
+    pub params: Vec<f32>,
+    /// Heuristics whose components are the input, when the network reads them.
+    pub heuristics: Arc<Vec<HeuristicRef>>,
+    /// Rescaling of heuristic components applied before they are read.
+    pub normalizer: Arc<Normalizer>,
+}
+
+impl MlpEvaluator {
+    /// Network with every parameter set to zero, a template for evolved ones.
+    #[must_use]
+    pub fn new(config: Arc<MlpConfig>, heuristics: Arc<Vec<HeuristicRef>>) -> Self {
+        let n_params = config.n_params(config.n_inputs(&heuristics));
+        Self {
+            config,
+            params: vec![0.; n_params],
+            heuristics,
+            normalizer: Arc::default(),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if `params` don't match the size of the network.
+    pub fn from_params(
+        config: Arc<MlpConfig>,
+        heuristics: Arc<Vec<HeuristicRef>>,
+        params: Vec<f32>,
+    ) -> Result<Self> {
+        let network = Self::new(config, heuristics);
+        if params.len() != network.params.len() {
+            bail!(
+                "Network has {} parameters, but {} were passed.",
+                network.params.len(),
+                params.len()
+            );
+        }
+        Ok(Self { params, ..network })
+    }
+
+    #[must_use]
+    pub fn with_normalizer(self, normalizer: Arc<Normalizer>) -> Self {
+        Self { normalizer, ..self }
+    }
+}
+
+impl Evaluator for MlpEvaluator {
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        let input = self.config.input(&self.heuristics, &self.normalizer, ctx);
+        self.config.forward(&self.params, &input)
+    }
+
+    /// Network isn't a sum of parts, it's only cached when it doesn't see the placement.
+    fn evaluate_cached(&self, game: &Game, cache: &mut SearchCache) -> HeuristicScore {
+        if self.config.depends_only_on_board(&self.heuristics) {
+            cache.evaluation(&game.board, || self.evaluate(&EvalContext::from_game(game)))
+        } else {
+            self.evaluate(&EvalContext::from_game(game))
+        }
+    }
+}
+
+impl Parameterized for MlpEvaluator {
+    fn params(&self) -> &[f32] {
+        &self.params
+    }
+
+    fn with_params(&self, params: Vec<f32>) -> Self {
+        Self {
+            params,
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tetris_core::prelude::*;
+    use tetris_heuristics::prelude::*;
+    use tetris_heuristics::HeuristicRegistry;
+
+    use super::{MlpConfig, MlpEvaluator, MlpInput};
+    use crate::{Evaluator, Parameterized, Policy, SearchCache, SearchConfig, SearchPolicy};
+
+    #[test]
+    fn test_n_params() {
+        let linear = MlpConfig::default();
+        assert_eq!(linear.n_params(5), 6);
+
+        let network = MlpConfig {
+            input: MlpInput::ColumnHeights,
+            hidden_layers: vec![4, 3],
+        };
+        assert_eq!(network.n_params(10), 11 * 4 + 5 * 3 + 4);
+    }
+
+    #[test]
+    fn test_forward() {
+        let network = MlpConfig {
+            input: MlpInput::Heuristics,
+            hidden_layers: vec![2],
+        };
+        // Hidden neurons: tanh(x + y) and tanh(-x), output: 2 * h1 - h2 + 0.5.
+        let params = [1., 1., 0., -1., 0., 0., 2., -1., 0.5];
+
+        let output = network.forward(&params, &[0.5, -0.25]);
+        let expected = 2_f32.mul_add(0.25_f32.tanh(), 0.5_f32.tanh()) + 0.5;
+        assert!((output - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_input() {
+        for input in [MlpInput::Heuristics, MlpInput::ColumnHeights] {
+            assert_eq!(input.to_string().parse::<MlpInput>().unwrap(), input);
+        }
+        assert!("heights".parse::<MlpInput>().is_err());
+    }
+
+    #[test]
+    fn test_mlp_evaluator_evaluates_with_its_parameters() {
+        let heuristics = HeuristicRegistry::default()
+            .select(&["holes", "bumpiness"])
+            .unwrap();
+        let network = MlpConfig {
+            input: MlpInput::ColumnHeights,
+            hidden_layers: vec![4],
+        };
+        let template = MlpEvaluator::new(Arc::new(network), Arc::new(heuristics));
+        assert_eq!(template.params().len(), 11 * 4 + 5);
+
+        let params = (0..template.params.len())
+            .map(|idx| (idx as f32).sin())
+            .collect();
+        let mlp = template.with_params(params);
+        let game =
+            SearchPolicy::new(&mlp, SearchConfig::with_depth(2)).play(Game::from_seed(0), Some(5));
+        assert_eq!(game.score.dropped_pieces, 5);
+
+        let mut cache = SearchCache::default();
+        let cached = mlp.evaluate_cached(&game, &mut cache);
+        assert!((cached - mlp.evaluate(&EvalContext::from_game(&game))).abs() < f32::EPSILON);
+        assert_eq!(cache.len(), 1);
+
+        let too_short = vec![0.; template.params.len() - 1];
+        let MlpEvaluator {
+            config, heuristics, ..
+        } = template;
+        assert!(MlpEvaluator::from_params(config, heuristics, too_short).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use tetris_heuristics::prelude::*;
+use tetris_heuristics::HeuristicRegistry;
+
+use crate::{Agent, Evaluator, MlpConfig, MlpEvaluator, Normalizer};
+
+/// Trained weights stored together with the names of heuristics they belong to.
+///
+/// Vector-valued heuristics own as many consecutive weights as they have components.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedModel {
+    pub heuristics: Vec<String>,
+    pub weights: Vec<f32>,
+    /// Normalization statistics of every weighed component, empty if values are used as they are.
+    #[serde(default, skip_serializing_if = "Normalizer::is_empty")]
+    pub normalization: Normalizer,
+    /// Network whose parameters are stored in `weights`, the model is linear without it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<MlpConfig>,
+}
+
+impl SavedModel {
+    #[must_use]
+    pub fn from_agent(agent: &Agent) -> Self {
+        Self {
+            heuristics: agent
+                .heuristics
+                .iter()
+                .map(|h| h.name().to_string())
+                .collect(),
+            weights: agent.weights.clone(),
+            normalization: Normalizer::clone(&agent.normalizer),
+            network: None,
+        }
+    }
+
+    #[must_use]
+    pub fn from_mlp(mlp: &MlpEvaluator) -> Self {
+        Self {
+            heuristics: mlp
+                .heuristics
+                .iter()
+                .map(|h| h.name().to_string())
+                .collect(),
+            weights: mlp.params.clone(),
+            normalization: Normalizer::clone(&mlp.normalizer),
+            network: Some(MlpConfig::clone(&mlp.config)),
+        }
+    }
+
+    #[must_use]
+    pub fn from_named_weights(named_weights: &[(&str, f32)]) -> Self {
+        let (heuristics, weights) = named_weights
+            .iter()
+            .map(|&(name, weight)| (name.to_string(), weight))
+            .unzip();
+
+        Self {
+            heuristics,
+            weights,
+            normalization: Normalizer::default(),
+            network: None,
+        }
+    }
+
+    /// Build a linear agent using heuristics looked up by name in `registry`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the model is a network,
+    /// if the number of weights, normalized components and heuristics' components differ,
+    /// or if any heuristic is unknown or repeated.
+    pub fn to_agent(&self, registry: &HeuristicRegistry) -> Result<Agent> {
+        if self.network.is_some() {
+            bail!("Model is a network, not a linear agent.");
+        }
+        let heuristics = self.select_heuristics(registry)?;
+
+        let agent = Agent::from_weights(self.weights.clone(), &heuristics)
+            .with_context(|| format!("Model uses heuristics {:?}", self.heuristics))?;
+        Ok(agent.with_normalizer(Arc::new(self.normalization.clone())))
+    }
+
+    /// Build the network stored in the model, reading heuristics looked up in `registry`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the model is linear,
+    /// if the number of network's parameters or normalized components doesn't match,
+    /// or if any heuristic is unknown or repeated.
+    pub fn to_mlp(&self, registry: &HeuristicRegistry) -> Result<MlpEvaluator> {
+        let Some(network) = &self.network else {
+            bail!("Model is linear, it has no network.");
+        };
+        let heuristics = self.select_heuristics(registry)?;
+
+        let mlp = MlpEvaluator::from_params(
+            Arc::new(network.clone()),
+            Arc::new(heuristics),
+            self.weights.clone(),
+        )?;
+        Ok(mlp.with_normalizer(Arc::new(self.normalization.clone())))
+    }
+
+    /// Build whichever evaluator the model stores, a network or a linear agent.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if [`SavedModel::to_agent`]
+    /// or [`SavedModel::to_mlp`] would.
+    pub fn to_evaluator(
+        &self,
+        registry: &HeuristicRegistry,
+    ) -> Result<Box<dyn Evaluator + Send + Sync>> {
+        Ok(if self.network.is_some() {
+            Box::new(self.to_mlp(registry)?)
+        } else {
+            Box::new(self.to_agent(registry)?)
+        })
+    }
+
+    /// Heuristics named by the model, which its normalization has to match.
+    fn select_heuristics(&self, registry: &HeuristicRegistry) -> Result<Vec<HeuristicRef>> {
+        let heuristics = registry.select(&self.heuristics)?;
+
+        let n_components = total_dimension(&heuristics);
+        if !self.normalization.is_empty() && self.normalization.len() != n_components {
+            bail!(
+                "Model normalizes {} components, but its heuristics have {n_components}.",
+                self.normalization.len()
+            );
+        }
+        Ok(heuristics)
+    }
+
+    /// Parse a model in the format written by [`SavedModel::save`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `json` isn't a valid model.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Parsing model")
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if the file can't be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("Creating {}", path.display()))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if the file can't be read or isn't a valid model.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening {}", path.display()))?;
+        let model = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Parsing model from {}", path.display()))?;
+        Ok(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_heuristics::HeuristicRegistry;
+
+    use std::sync::Arc;
+
+    use super::SavedModel;
+    use crate::{
+        sample_states, MlpConfig, MlpEvaluator, MlpInput, NormalizationMode, Normalizer,
+        Parameterized,
+    };
+
+    #[test]
+    fn test_model_round_trip_through_agent() {
+        let model = SavedModel::from_named_weights(&[("holes", 0.5), ("bumpiness", -0.25)]);
+        let agent = model.to_agent(&HeuristicRegistry::default()).unwrap();
+
+        assert_eq!(
+            agent.named_weights(),
+            [("holes".to_string(), 0.5), ("bumpiness".to_string(), -0.25)]
+        );
+        assert_eq!(SavedModel::from_agent(&agent), model);
+    }
+
+    #[test]
+    fn test_mismatched_model_is_rejected() {
+        let registry = HeuristicRegistry::default();
+
+        let mut missing_weight = SavedModel::from_named_weights(&[("holes", 0.5)]);
+        missing_weight.heuristics.push("bumpiness".to_string());
+        assert!(missing_weight.to_agent(&registry).is_err());
+
+        let unknown = SavedModel::from_named_weights(&[("not_a_heuristic", 0.5)]);
+        assert!(unknown.to_agent(&registry).is_err());
+    }
+
+    #[test]
+    fn test_normalization_is_saved_with_model() {
+        let registry = HeuristicRegistry::default();
+        let mut model = SavedModel::from_named_weights(&[("holes", 0.5), ("bumpiness", -0.25)]);
+        let heuristics = registry.select(&model.heuristics).unwrap();
+        model.normalization = Normalizer::fit(
+            &heuristics,
+            &[NormalizationMode::ZScore, NormalizationMode::Static],
+            &sample_states(1, 10, 0),
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&model).unwrap();
+        let loaded: SavedModel = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, model);
+
+        let agent = loaded.to_agent(&registry).unwrap();
+        assert_eq!(SavedModel::from_agent(&agent), model);
+
+        model.normalization = Normalizer::default();
+        let plain = model.to_agent(&registry).unwrap();
+        let state = sample_states(1, 5, 1).pop().unwrap();
+        assert!((plain.forward_with_game(&state) - agent.forward_with_game(&state)).abs() > 0.);
+    }
+
+    #[test]
+    fn test_network_is_saved_with_model() {
+        let registry = HeuristicRegistry::default();
+        let heuristics = registry.select(&["holes", "bumpiness"]).unwrap();
+        let network = MlpConfig {
+            input: MlpInput::Heuristics,
+            hidden_layers: vec![3],
+        };
+        let template = MlpEvaluator::new(Arc::new(network), Arc::new(heuristics));
+        let mlp = template.with_params((0..template.params.len()).map(|idx| idx as f32).collect());
+
+        let model = SavedModel::from_mlp(&mlp);
+        let json = serde_json::to_string(&model).unwrap();
+        let loaded: SavedModel = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, model);
+        assert_eq!(loaded.to_mlp(&registry).unwrap().params, mlp.params);
+        assert!(loaded.to_agent(&registry).is_err());
+        assert!(loaded.to_evaluator(&registry).is_ok());
+
+        let mut truncated = model;
+        truncated.weights.pop();
+        assert!(truncated.to_mlp(&registry).is_err());
+
+        let linear = SavedModel::from_named_weights(&[("holes", 0.5)]);
+        assert!(linear.to_mlp(&registry).is_err());
+    }
+}
@@ -1,5 +1,14 @@
 use anyhow::{bail, Result};
-use tetris_heuristics::Heuristic;
+use tetris_heuristics::HeuristicRef;
+
+use crate::{
+    sample_states, Crossover, MctsConfig, MlpConfig, Mutation, NormalizationMode, Normalizer,
+    Optimizer, SearchConfig, Selection,
+};
+
+/// Games played by the El-Tetris agent to sample boards for normalization statistics.
+const NORMALIZATION_GAMES: usize = 4;
+const NORMALIZATION_MOVES: usize = 100;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -8,10 +17,50 @@ pub struct Config {
     pub max_drops: Option<usize>,
     pub max_populations: Option<usize>,
     pub max_non_progress_populations: Option<usize>,
-    pub heuristics_used: Vec<Heuristic>,
+    pub heuristics_used: Vec<HeuristicRef>,
+    /// One mode per heuristic, or empty to use heuristic values as they are.
+    pub normalization: Vec<NormalizationMode>,
+    /// Lookahead used by agents while their fitness is measured.
+    pub search: SearchConfig,
+    /// Monte Carlo tree search used instead of `search` when set.
+    pub mcts: Option<MctsConfig>,
+    /// Network whose parameters are evolved instead of the weights of heuristics, when set.
+    pub network: Option<MlpConfig>,
+    /// How every generation is derived from the previous one.
+    pub optimizer: Optimizer,
+    /// How the genetic algorithm chooses parents.
+    pub selection: Selection,
+    /// Number of best agents the genetic algorithm copies to the next generation unchanged.
+    pub elite: usize,
+    /// How the genetic algorithm combines the weights of parents.
+    pub crossover: Crossover,
+    /// How the genetic algorithm changes the weights of offspring.
+    pub mutation: Mutation,
+    /// Whether the genetic algorithm keeps weights on the unit sphere,
+    /// which doesn't change the moves of linear agents.
+    pub normalize_weights: bool,
 }
 
 impl Config {
+    /// Statistics of `heuristics_used` in their `normalization` modes,
+    /// fitted to boards seen by the El-Tetris agent if any mode needs them.
+    /// Empty without any modes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the number of modes doesn't match the heuristics.
+    pub fn fit_normalizer(&self) -> Result<Normalizer> {
+        if self.normalization.is_empty() {
+            return Ok(Normalizer::default());
+        }
+        let states = if self.normalization.iter().any(|mode| mode.needs_samples()) {
+            sample_states(NORMALIZATION_GAMES, NORMALIZATION_MOVES, 0)
+        } else {
+            Vec::new()
+        };
+        Normalizer::fit(&self.heuristics_used, &self.normalization, &states)
+    }
+
     /// Validate the state of this [`Config`].
     ///
     /// # Errors
@@ -20,6 +69,16 @@ impl Config {
     /// - `n_entities` is 0
     /// - `n_entities` is an odd number (because current implementation doesn't work with odd number of entities)
     /// - `heuristics_used` used are empty
+    /// - `normalization` is neither empty nor has one mode per heuristic
+    /// - `search` is invalid, see [`SearchConfig::validate`]
+    /// - any hidden layer of `network` is empty
+    /// - parameters of `optimizer`, `selection`, `crossover` or `mutation` are invalid,
+    ///   see [`Optimizer::validate`], [`Selection::validate`], [`Crossover::validate`]
+    ///   and [`Mutation::validate`]
+    /// - weights of a `network` are normalized
+    /// - `elite` isn't smaller than `n_entities`
+    /// - `selection`, `elite`, `crossover`, `mutation` or `normalize_weights` aren't the defaults
+    ///   while `optimizer` isn't [`Optimizer::Genetic`], which is the only one using them
     pub fn validate(&self) -> Result<()> {
         if self.n_entities == 0 {
             bail!("N entities cannot be 0.")
@@ -30,6 +89,46 @@ impl Config {
         if self.heuristics_used.is_empty() {
             bail!("Heuristics cannot be empty!")
         }
+        if !self.normalization.is_empty() && self.normalization.len() != self.heuristics_used.len()
+        {
+            bail!(
+                "Got {} normalization modes for {} heuristics.",
+                self.normalization.len(),
+                self.heuristics_used.len()
+            )
+        }
+        self.search.validate()?;
+        if let Some(network) = &self.network {
+            if network.hidden_layers.contains(&0) {
+                bail!("Hidden layers of the network cannot be empty.")
+            }
+        }
+        self.optimizer.validate()?;
+        self.selection.validate()?;
+        self.crossover.validate()?;
+        self.mutation.validate()?;
+        if self.normalize_weights && self.network.is_some() {
+            bail!("Normalizing parameters would change what the network evaluates.")
+        }
+        if self.elite >= self.n_entities {
+            bail!(
+                "Elite of {} agents leaves no room for offspring in population of {}.",
+                self.elite,
+                self.n_entities
+            )
+        }
+        let genetic_defaults = self.selection == Selection::default()
+            && self.elite == 0
+            && self.crossover == Crossover::default()
+            && self.mutation == Mutation::default()
+            && !self.normalize_weights;
+        if self.optimizer != Optimizer::Genetic && !genetic_defaults {
+            bail!(
+                "Selection, elite, crossover, mutation and weights normalization \
+                 only apply to the genetic algorithm, not to {}.",
+                self.optimizer
+            )
+        }
         Ok(())
     }
 }
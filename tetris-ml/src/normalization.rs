@@ -0,0 +1,272 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tetris_core::prelude::*;
+use tetris_heuristics::prelude::*;
+
+use crate::{Agent, Evaluator, Policy, SearchConfig, SearchPolicy};
+
+/// How the values of a single heuristic are rescaled before they are weighed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NormalizationMode {
+    #[default]
+    None,
+    /// Map the heuristic's declared [`Heuristic::range`] onto `0..=1`.
+    Static,
+    /// Subtract the mean and divide by the standard deviation measured on sampled boards.
+    ZScore,
+    /// Map the minimum and maximum measured on sampled boards onto `0..=1`.
+    MinMax,
+}
+
+impl NormalizationMode {
+    /// Whether statistics of sampled boards are needed to normalize values this way.
+    #[must_use]
+    pub const fn needs_samples(self) -> bool {
+        matches!(self, Self::ZScore | Self::MinMax)
+    }
+}
+
+impl FromStr for NormalizationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "static" => Ok(Self::Static),
+            "z-score" => Ok(Self::ZScore),
+            "min-max" => Ok(Self::MinMax),
+            _ => bail!("Unknown normalization {s:?}, available: none, static, z-score, min-max."),
+        }
+    }
+}
+
+/// Rescaling of a single heuristic component, together with the statistics it needs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Normalization {
+    #[default]
+    None,
+    Static {
+        min: f32,
+        max: f32,
+    },
+    ZScore {
+        mean: f32,
+        std_dev: f32,
+    },
+    MinMax {
+        min: f32,
+        max: f32,
+    },
+}
+
+impl Normalization {
+    #[must_use]
+    pub fn apply(&self, value: HeuristicScore) -> HeuristicScore {
+        match *self {
+            Self::None => value,
+            Self::Static { min, max } | Self::MinMax { min, max } => {
+                if max > min {
+                    (value - min) / (max - min)
+                } else {
+                    value - min
+                }
+            }
+            Self::ZScore { mean, std_dev } => {
+                if std_dev > 0. {
+                    (value - mean) / std_dev
+                } else {
+                    value - mean
+                }
+            }
+        }
+    }
+}
+
+/// Normalization of every heuristic component an agent weighs, in the order of its weights.
+///
+/// Empty normalizer leaves all values unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Normalizer {
+    components: Vec<Normalization>,
+}
+
+impl Normalizer {
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Normalize the value of the `idx`-th component.
+    #[must_use]
+    pub fn apply(&self, idx: usize, value: HeuristicScore) -> HeuristicScore {
+        self.components
+            .get(idx)
+            .map_or(value, |normalization| normalization.apply(value))
+    }
+
+    /// Compute statistics for every heuristic component from `states`,
+    /// with one mode per heuristic.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the number of modes and heuristics differ,
+    /// or if statistics are needed but `states` is empty.
+    pub fn fit(
+        heuristics: &[HeuristicRef],
+        modes: &[NormalizationMode],
+        states: &[Game],
+    ) -> Result<Self> {
+        if heuristics.len() != modes.len() {
+            bail!(
+                "Got {} normalization modes for {} heuristics.",
+                modes.len(),
+                heuristics.len()
+            );
+        }
+
+        let needs_samples = modes.iter().any(|mode| mode.needs_samples());
+        if needs_samples && states.is_empty() {
+            bail!("Normalization statistics can't be computed without sampled states.");
+        }
+
+        let samples: Vec<Vec<HeuristicScore>> = states
+            .iter()
+            .map(|game| evaluate_components(heuristics, &EvalContext::from_game(game)))
+            .collect();
+        let column = |idx: usize| samples.iter().map(move |sample| sample[idx]);
+
+        let mut components = Vec::with_capacity(total_dimension(heuristics));
+        for (h, mode) in heuristics.iter().zip(modes) {
+            for _ in 0..h.dimension() {
+                let idx = components.len();
+                components.push(match mode {
+                    NormalizationMode::None => Normalization::None,
+                    NormalizationMode::Static => Normalization::Static {
+                        min: *h.range().start(),
+                        max: *h.range().end(),
+                    },
+                    NormalizationMode::ZScore => {
+                        let n = samples.len() as f32;
+                        let mean = column(idx).sum::<f32>() / n;
+                        let variance = column(idx).map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+                        Normalization::ZScore {
+                            mean,
+                            std_dev: variance.sqrt(),
+                        }
+                    }
+                    NormalizationMode::MinMax => Normalization::MinMax {
+                        min: column(idx).fold(f32::INFINITY, f32::min),
+                        max: column(idx).fold(f32::NEG_INFINITY, f32::max),
+                    },
+                });
+            }
+        }
+
+        Ok(Self { components })
+    }
+}
+
+/// Candidate states seen by the El-Tetris agent during `n_games` games of `n_moves` moves,
+/// a mix of good and bad boards similar to the ones agents evaluate.
+#[must_use]
+pub fn sample_states(n_games: usize, n_moves: usize, seed: u64) -> Vec<Game> {
+    sample_states_with(&Agent::el_tetris(), n_games, n_moves, seed)
+}
+
+/// Candidate states seen by `evaluator` during `n_games` greedy games of `n_moves` moves,
+/// each game starting from an empty board.
+#[must_use]
+pub fn sample_states_with<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    n_games: usize,
+    n_moves: usize,
+    seed: u64,
+) -> Vec<Game> {
+    let policy = SearchPolicy::new(evaluator, SearchConfig::GREEDY);
+    let mut states = Vec::new();
+
+    for game_idx in 0..n_games as u64 {
+        let mut game = Game::from_seed(seed.wrapping_add(game_idx));
+
+        for _ in 0..n_moves {
+            states.extend(Agent::get_all_possible_next_game_states(&game));
+
+            let Some(next) = policy.choose(&game) else {
+                break;
+            };
+            game = next;
+        }
+    }
+
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tetris_heuristics::HeuristicRegistry;
+
+    use super::{sample_states, Normalization, NormalizationMode, Normalizer};
+    use crate::Agent;
+
+    #[test]
+    fn test_apply_normalization() {
+        let min_max = Normalization::MinMax { min: 2., max: 6. };
+        assert!((min_max.apply(4.) - 0.5).abs() < f32::EPSILON);
+
+        let z_score = Normalization::ZScore {
+            mean: 10.,
+            std_dev: 2.,
+        };
+        assert!((z_score.apply(6.) + 2.).abs() < f32::EPSILON);
+
+        // Constant heuristic is only shifted.
+        let constant = Normalization::ZScore {
+            mean: 1.,
+            std_dev: 0.,
+        };
+        assert!(constant.apply(1.).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_fit_on_sampled_states() {
+        let heuristics = HeuristicRegistry::default()
+            .select(&["holes", "bumpiness", "row_transitions"])
+            .unwrap();
+        let modes = [
+            NormalizationMode::ZScore,
+            NormalizationMode::MinMax,
+            NormalizationMode::Static,
+        ];
+        let states = sample_states(2, 20, 0);
+
+        let normalizer = Normalizer::fit(&heuristics, &modes, &states).unwrap();
+        assert_eq!(normalizer.len(), 3);
+        assert_eq!(
+            normalizer.components[2],
+            Normalization::Static { min: 0., max: 220. }
+        );
+
+        let agent = Agent::from_weights(vec![1., 0., 0.], &heuristics)
+            .unwrap()
+            .with_normalizer(Arc::new(normalizer));
+        let normalized_holes: Vec<f32> =
+            states.iter().map(|g| agent.forward_with_game(g)).collect();
+
+        let mean = normalized_holes.iter().sum::<f32>() / normalized_holes.len() as f32;
+        assert!(mean.abs() < 1e-3, "mean of z-scores is {mean}");
+
+        assert!(Normalizer::fit(&heuristics, &modes[..1], &states).is_err());
+        assert!(Normalizer::fit(&heuristics, &modes, &[]).is_err());
+    }
+}
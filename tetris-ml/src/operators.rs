@@ -0,0 +1,367 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use rand::{Rng, RngCore};
+use rand_distr::StandardNormal;
+
+/// Combines the weights of two parents into the weights of their two children.
+pub trait CrossoverOperator {
+    fn cross(&self, first: &[f32], second: &[f32], rng: &mut dyn RngCore) -> (Vec<f32>, Vec<f32>);
+}
+
+/// Randomly changes the weights of an offspring.
+pub trait MutationOperator {
+    /// Mutate `weights` of an agent, which mutates with probability `rate`.
+    ///
+    /// `step_size` is the entity's own mutation strength, evolved along with its weights.
+    fn mutate(&self, weights: &mut [f32], step_size: &mut f32, rate: f64, rng: &mut dyn RngCore);
+}
+
+/// Crossover of the genetic algorithm.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Crossover {
+    /// Every weight of a child is a random convex combination of its parents' weights.
+    #[default]
+    Blend,
+    /// Every weight is swapped between the children with probability one half.
+    Uniform,
+    /// Weights after a random cut point are swapped between the children.
+    OnePoint,
+    /// Simulated binary crossover, children are closer to their parents for bigger `eta`.
+    Sbx { eta: f32 },
+}
+
+impl Crossover {
+    pub const DEFAULT_SBX_ETA: f32 = 2.;
+
+    /// # Errors
+    ///
+    /// This function will return an error if the distribution index of SBX is negative.
+    pub fn validate(&self) -> Result<()> {
+        if let Self::Sbx { eta } = *self {
+            if eta.is_nan() || eta < 0. {
+                bail!("Distribution index of SBX must be non-negative, got {eta}.")
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CrossoverOperator for Crossover {
+    fn cross(&self, first: &[f32], second: &[f32], rng: &mut dyn RngCore) -> (Vec<f32>, Vec<f32>) {
+        let mut first = first.to_vec();
+        let mut second = second.to_vec();
+
+        match *self {
+            Self::Blend => {
+                for (w1, w2) in first.iter_mut().zip(&mut second) {
+                    let alpha: f32 = rng.gen_range(0.0..1.0);
+                    let beta: f32 = rng.gen_range(0.0..1.0);
+                    let (parent1, parent2) = (*w1, *w2);
+                    *w1 = alpha.mul_add(parent1, (1. - alpha) * parent2);
+                    *w2 = beta.mul_add(parent2, (1. - beta) * parent1);
+                }
+            }
+            Self::Uniform => {
+                for (w1, w2) in first.iter_mut().zip(&mut second) {
+                    if rng.gen_bool(0.5) {
+                        std::mem::swap(w1, w2);
+                    }
+                }
+            }
+            Self::OnePoint => {
+                let cut = rng.gen_range(0..=first.len());
+                first[cut..].swap_with_slice(&mut second[cut..]);
+            }
+            Self::Sbx { eta } => {
+                for (w1, w2) in first.iter_mut().zip(&mut second) {
+                    let u: f32 = rng.gen_range(0.0..1.0);
+                    let spread = if u <= 0.5 {
+                        (2. * u).powf((eta + 1.).recip())
+                    } else {
+                        (0.5 / (1. - u)).powf((eta + 1.).recip())
+                    };
+                    let (mean, half_diff) = (f32::midpoint(*w1, *w2), (*w1 - *w2) / 2.);
+                    (*w1, *w2) = (
+                        spread.mul_add(half_diff, mean),
+                        spread.mul_add(-half_diff, mean),
+                    );
+                }
+            }
+        }
+
+        (first, second)
+    }
+}
+
+impl Display for Crossover {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Blend => write!(f, "blend"),
+            Self::Uniform => write!(f, "uniform"),
+            Self::OnePoint => write!(f, "one-point"),
+            Self::Sbx { eta } => write!(f, "sbx:{eta}"),
+        }
+    }
+}
+
+impl FromStr for Crossover {
+    type Err = anyhow::Error;
+
+    /// Parse `blend`, `uniform`, `one-point` or `sbx[:eta]`, e.g. `sbx:5`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, param) = split_param(s);
+
+        let crossover = match (name, param) {
+            ("blend", None) => Self::Blend,
+            ("uniform", None) => Self::Uniform,
+            ("one-point", None) => Self::OnePoint,
+            ("sbx", eta) => Self::Sbx {
+                eta: parse_param(name, eta, Self::DEFAULT_SBX_ETA)?,
+            },
+            ("blend" | "uniform" | "one-point", Some(_)) => {
+                bail!("Crossover {name} takes no parameters.")
+            }
+            _ => bail!("Unknown crossover {name:?}, expected blend, uniform, one-point or sbx."),
+        };
+
+        crossover.validate()?;
+        Ok(crossover)
+    }
+}
+
+/// Mutation of the genetic algorithm.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Mutation {
+    /// A single random weight is shifted by a uniform value from `-1..=1`.
+    #[default]
+    Uniform,
+    /// Every weight is shifted by normal noise with standard deviation `sigma`.
+    Gaussian { sigma: f32 },
+    /// Every weight is shifted by normal noise on its own, with probability of the mutation rate.
+    PerGene { sigma: f32 },
+    /// Every weight is shifted by normal noise of the entity's step size, starting at `sigma`,
+    /// which is itself mutated log-normally before it's used.
+    SelfAdaptive { sigma: f32 },
+}
+
+impl Mutation {
+    pub const DEFAULT_SIGMA: f32 = 0.1;
+
+    /// Step size entities start with, which only self-adaptive mutation changes.
+    #[must_use]
+    pub const fn initial_step_size(&self) -> f32 {
+        match *self {
+            Self::Uniform => 1.,
+            Self::Gaussian { sigma } | Self::PerGene { sigma } | Self::SelfAdaptive { sigma } => {
+                sigma
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if `sigma` isn't positive.
+    pub fn validate(&self) -> Result<()> {
+        let sigma = self.initial_step_size();
+        if sigma.is_nan() || sigma <= 0. {
+            bail!("Sigma of mutation must be positive, got {sigma}.")
+        }
+        Ok(())
+    }
+}
+
+impl MutationOperator for Mutation {
+    fn mutate(&self, weights: &mut [f32], step_size: &mut f32, rate: f64, rng: &mut dyn RngCore) {
+        match *self {
+            Self::PerGene { sigma } => {
+                for weight in weights.iter_mut() {
+                    if rng.gen_bool(rate) {
+                        *weight += sigma * rng.sample::<f32, _>(StandardNormal);
+                    }
+                }
+            }
+            _ if !rng.gen_bool(rate) => {}
+            Self::Uniform => {
+                if !weights.is_empty() {
+                    let idx = rng.gen_range(0..weights.len());
+                    weights[idx] += rng.gen_range(-1.0..=1.0);
+                }
+            }
+            Self::Gaussian { sigma } => {
+                for weight in weights.iter_mut() {
+                    *weight += sigma * rng.sample::<f32, _>(StandardNormal);
+                }
+            }
+            Self::SelfAdaptive { .. } => {
+                // Learning rate of the step size recommended for a single sigma per agent.
+                let tau = (weights.len().max(1) as f32).sqrt().recip();
+                *step_size *= (tau * rng.sample::<f32, _>(StandardNormal)).exp();
+                for weight in weights.iter_mut() {
+                    *weight += *step_size * rng.sample::<f32, _>(StandardNormal);
+                }
+            }
+        }
+    }
+}
+
+impl Display for Mutation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Uniform => write!(f, "uniform"),
+            Self::Gaussian { sigma } => write!(f, "gaussian:{sigma}"),
+            Self::PerGene { sigma } => write!(f, "per-gene:{sigma}"),
+            Self::SelfAdaptive { sigma } => write!(f, "self-adaptive:{sigma}"),
+        }
+    }
+}
+
+impl FromStr for Mutation {
+    type Err = anyhow::Error;
+
+    /// Parse `uniform`, `gaussian[:sigma]`, `per-gene[:sigma]` or `self-adaptive[:sigma]`,
+    /// e.g. `gaussian:0.2`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, param) = split_param(s);
+        let sigma = || parse_param(name, param, Self::DEFAULT_SIGMA);
+
+        let mutation = match (name, param) {
+            ("uniform", None) => Self::Uniform,
+            ("uniform", Some(_)) => bail!("Mutation uniform takes no parameters."),
+            ("gaussian", _) => Self::Gaussian { sigma: sigma()? },
+            ("per-gene", _) => Self::PerGene { sigma: sigma()? },
+            ("self-adaptive", _) => Self::SelfAdaptive { sigma: sigma()? },
+            _ => bail!(
+                "Unknown mutation {name:?}, expected uniform, gaussian, per-gene or self-adaptive."
+            ),
+        };
+
+        mutation.validate()?;
+        Ok(mutation)
+    }
+}
+
+/// Scale `weights` to the unit sphere, which doesn't change which state a linear agent prefers.
+pub fn normalize(weights: &mut [f32]) {
+    let norm = weights
+        .iter()
+        .map(|weight| weight * weight)
+        .sum::<f32>()
+        .sqrt();
+    if norm > 0. {
+        for weight in weights {
+            *weight /= norm;
+        }
+    }
+}
+
+fn split_param(s: &str) -> (&str, Option<&str>) {
+    match s.split_once(':') {
+        Some((name, param)) => (name, Some(param)),
+        None => (s, None),
+    }
+}
+
+fn parse_param(name: &str, param: Option<&str>, default: f32) -> Result<f32> {
+    param
+        .map_or(Ok(default), str::parse)
+        .with_context(|| format!("Invalid parameter {param:?} of operator {name}."))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{normalize, Crossover, CrossoverOperator, Mutation, MutationOperator};
+
+    const FIRST: [f32; 4] = [1., 2., 3., 4.];
+    const SECOND: [f32; 4] = [-1., -2., -3., -4.];
+
+    fn n_changed(weights: &[f32], original: &[f32]) -> usize {
+        weights
+            .iter()
+            .zip(original)
+            .filter(|(w, o)| (*w - *o).abs() > 1e-6)
+            .count()
+    }
+
+    #[test]
+    fn test_parse_operators() {
+        assert_eq!(
+            "sbx".parse::<Crossover>().unwrap(),
+            Crossover::Sbx { eta: 2. }
+        );
+        assert_eq!(
+            "gaussian:0.5".parse::<Mutation>().unwrap(),
+            Mutation::Gaussian { sigma: 0.5 }
+        );
+        for crossover in [
+            Crossover::Blend,
+            Crossover::OnePoint,
+            Crossover::Sbx { eta: 3. },
+        ] {
+            assert_eq!(
+                crossover.to_string().parse::<Crossover>().unwrap(),
+                crossover
+            );
+        }
+        let mutation = Mutation::SelfAdaptive { sigma: 0.3 };
+        assert_eq!(mutation.to_string().parse::<Mutation>().unwrap(), mutation);
+
+        assert!("sbx:-1".parse::<Crossover>().is_err());
+        assert!("uniform:2".parse::<Crossover>().is_err());
+        assert!("per-gene:0".parse::<Mutation>().is_err());
+        assert!("cauchy".parse::<Mutation>().is_err());
+    }
+
+    #[test]
+    fn test_crossovers_keep_parents_genes() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for crossover in [Crossover::Uniform, Crossover::OnePoint] {
+            let (first, second) = crossover.cross(&FIRST, &SECOND, &mut rng);
+            let abs: Vec<f32> = first.iter().map(|w| w.abs()).collect();
+            let negated: Vec<f32> = second.iter().map(|w| -w).collect();
+            assert_eq!(n_changed(&abs, &FIRST), 0);
+            assert_eq!(n_changed(&negated, &first), 0);
+        }
+
+        // Children of SBX are symmetric around their parents' mean.
+        let (first, second) = Crossover::Sbx { eta: 2. }.cross(&FIRST, &SECOND, &mut rng);
+        assert!(first
+            .iter()
+            .zip(&second)
+            .all(|(w1, w2)| (w1 + w2).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_mutations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut step_size = 0.5;
+
+        let mut weights = FIRST;
+        Mutation::Gaussian { sigma: 1. }.mutate(&mut weights, &mut step_size, 0., &mut rng);
+        assert_eq!(n_changed(&weights, &FIRST), 0);
+
+        Mutation::Uniform.mutate(&mut weights, &mut step_size, 1., &mut rng);
+        assert_eq!(n_changed(&weights, &FIRST), 1);
+
+        let mut weights = FIRST;
+        Mutation::SelfAdaptive { sigma: 0.5 }.mutate(&mut weights, &mut step_size, 1., &mut rng);
+        assert!((step_size - 0.5).abs() > 1e-6);
+        assert_eq!(n_changed(&weights, &FIRST), FIRST.len());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut weights = [3., 0., -4.];
+        normalize(&mut weights);
+        assert_eq!(n_changed(&weights, &[0.6, 0., -0.8]), 0);
+
+        let mut zeros = [0.; 3];
+        normalize(&mut zeros);
+        assert_eq!(n_changed(&zeros, &[0.; 3]), 0);
+    }
+}
@@ -0,0 +1,523 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use rand::Rng;
+use rand_distr::StandardNormal;
+
+/// How the weights of the next generation are chosen after a generation has been evaluated.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Optimizer {
+    /// Selection, crossover and mutation of the configured kinds, see [`crate::Selection`],
+    /// [`crate::Crossover`] and [`crate::Mutation`].
+    #[default]
+    Genetic,
+    /// Noisy cross-entropy method: independent normal distributions fitted to the best
+    /// `elite_fraction` of the agents, with `noise / generation` added to their variances.
+    CrossEntropy { elite_fraction: f64, noise: f64 },
+    /// Covariance matrix adaptation evolution strategy, starting with step size `sigma`.
+    CmaEs { sigma: f64 },
+}
+
+impl Optimizer {
+    pub const DEFAULT_ELITE_FRACTION: f64 = 0.1;
+    pub const DEFAULT_NOISE: f64 = 0.1;
+    pub const DEFAULT_SIGMA: f64 = 0.5;
+
+    /// # Errors
+    ///
+    /// This function will return an error if the elite fraction isn't in `(0, 1]`,
+    /// the noise is negative, or the step size isn't positive.
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            Self::Genetic => {}
+            Self::CrossEntropy {
+                elite_fraction,
+                noise,
+            } => {
+                if !(elite_fraction > 0. && elite_fraction <= 1.) {
+                    bail!("Elite fraction must be in (0, 1], got {elite_fraction}.")
+                }
+                if noise.is_nan() || noise < 0. {
+                    bail!("Noise cannot be negative, got {noise}.")
+                }
+            }
+            Self::CmaEs { sigma } => {
+                if sigma.is_nan() || sigma <= 0. {
+                    bail!("Step size must be positive, got {sigma}.")
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Optimizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Genetic => write!(f, "ga"),
+            Self::CrossEntropy {
+                elite_fraction,
+                noise,
+            } => write!(f, "cem:{elite_fraction}:{noise}"),
+            Self::CmaEs { sigma } => write!(f, "cma-es:{sigma}"),
+        }
+    }
+}
+
+impl FromStr for Optimizer {
+    type Err = anyhow::Error;
+
+    /// Parse `ga`, `cem[:elite_fraction[:noise]]` or `cma-es[:sigma]`, e.g. `cem:0.2:0.5`.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        let mut param = |default: f64| -> Result<f64> {
+            parts.next().map_or(Ok(default), |value| {
+                value
+                    .parse()
+                    .with_context(|| format!("Invalid parameter {value:?} of optimizer {name}."))
+            })
+        };
+
+        let optimizer = match name {
+            "ga" => Self::Genetic,
+            "cem" => Self::CrossEntropy {
+                elite_fraction: param(Self::DEFAULT_ELITE_FRACTION)?,
+                noise: param(Self::DEFAULT_NOISE)?,
+            },
+            "cma-es" => Self::CmaEs {
+                sigma: param(Self::DEFAULT_SIGMA)?,
+            },
+            _ => bail!("Unknown optimizer {name:?}, expected ga, cem or cma-es."),
+        };
+
+        if parts.next().is_some() {
+            bail!("Too many parameters of optimizer {name}.");
+        }
+        optimizer.validate()?;
+        Ok(optimizer)
+    }
+}
+
+/// Distribution the weights of a generation are sampled from,
+/// updated with the weights of the evaluated generation.
+#[derive(Debug, Clone)]
+pub enum Strategy {
+    CrossEntropy(CrossEntropy),
+    CmaEs(CmaEs),
+}
+
+impl Strategy {
+    /// Strategy of `optimizer` for `dimension` weights, `None` for the genetic algorithm.
+    #[must_use]
+    pub fn new(optimizer: Optimizer, dimension: usize, population_size: usize) -> Option<Self> {
+        match optimizer {
+            Optimizer::Genetic => None,
+            Optimizer::CrossEntropy {
+                elite_fraction,
+                noise,
+            } => Some(Self::CrossEntropy(CrossEntropy::new(
+                dimension,
+                elite_fraction,
+                noise,
+            ))),
+            Optimizer::CmaEs { sigma } => {
+                Some(Self::CmaEs(CmaEs::new(dimension, sigma, population_size)))
+            }
+        }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> Vec<f32> {
+        match self {
+            Self::CrossEntropy(cem) => cem.sample(rng),
+            Self::CmaEs(cma) => cma.sample(rng),
+        }
+    }
+
+    /// Move the distribution towards the weights of the evaluated generation, best ones first.
+    pub fn update(&mut self, ranked: &[&[f32]]) {
+        match self {
+            Self::CrossEntropy(cem) => cem.update(ranked),
+            Self::CmaEs(cma) => cma.update(ranked),
+        }
+    }
+
+    /// Mean standard deviation of the sampled weights.
+    #[must_use]
+    pub fn spread(&self) -> f64 {
+        match self {
+            Self::CrossEntropy(cem) => cem.spread(),
+            Self::CmaEs(cma) => cma.spread(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CrossEntropy {
+    mean: Vec<f64>,
+    variance: Vec<f64>,
+    elite_fraction: f64,
+    noise: f64,
+    generation: usize,
+}
+
+impl CrossEntropy {
+    /// Standard normal distribution of `dimension` weights.
+    #[must_use]
+    pub fn new(dimension: usize, elite_fraction: f64, noise: f64) -> Self {
+        Self {
+            mean: vec![0.; dimension],
+            variance: vec![1.; dimension],
+            elite_fraction,
+            noise,
+            generation: 0,
+        }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> Vec<f32> {
+        self.mean
+            .iter()
+            .zip(&self.variance)
+            .map(|(mean, variance)| {
+                let z: f64 = rng.sample(StandardNormal);
+                variance.sqrt().mul_add(z, *mean) as f32
+            })
+            .collect()
+    }
+
+    pub fn update(&mut self, ranked: &[&[f32]]) {
+        let n_elite = (ranked.len() as f64 * self.elite_fraction).round() as usize;
+        let elite = &ranked[..n_elite.clamp(1, ranked.len())];
+        let n = elite.len() as f64;
+
+        self.generation += 1;
+        let noise = self.noise / self.generation as f64;
+
+        for (idx, (mean, variance)) in self.mean.iter_mut().zip(&mut self.variance).enumerate() {
+            *mean = elite.iter().map(|w| f64::from(w[idx])).sum::<f64>() / n;
+            *variance = elite
+                .iter()
+                .map(|w| (f64::from(w[idx]) - *mean).powi(2))
+                .sum::<f64>()
+                / n
+                + noise;
+        }
+    }
+
+    #[must_use]
+    pub fn spread(&self) -> f64 {
+        mean_sqrt(&self.variance)
+    }
+}
+
+/// CMA-ES as described in Hansen's "The CMA Evolution Strategy: A Tutorial",
+/// ranking agents by fitness instead of minimizing a function.
+#[derive(Debug, Clone)]
+pub struct CmaEs {
+    mean: Vec<f64>,
+    sigma: f64,
+    covariance: Vec<Vec<f64>>,
+    /// Eigenvectors of `covariance` as columns.
+    eigenvectors: Vec<Vec<f64>>,
+    /// Square roots of the eigenvalues of `covariance`.
+    scales: Vec<f64>,
+    path_sigma: Vec<f64>,
+    path_c: Vec<f64>,
+    generation: usize,
+    /// Recombination weights of the best half of the generation.
+    weights: Vec<f64>,
+    mu_eff: f64,
+    cc: f64,
+    cs: f64,
+    c1: f64,
+    cmu: f64,
+    damps: f64,
+    chi_n: f64,
+}
+
+impl CmaEs {
+    /// Isotropic distribution of `dimension` weights around 0,
+    /// adapted with generations of `population_size` agents.
+    #[must_use]
+    pub fn new(dimension: usize, sigma: f64, population_size: usize) -> Self {
+        let n = dimension as f64;
+        let mu = (population_size / 2).max(1);
+
+        let raw: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / total).collect();
+        let mu_eff = 1. / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let cc = (4. + mu_eff / n) / (n + 4. + 2. * mu_eff / n);
+        let cs = (mu_eff + 2.) / (n + mu_eff + 5.);
+        let c1 = 2. / (n + 1.3).mul_add(n + 1.3, mu_eff);
+        let cmu =
+            (1. - c1).min(2. * (mu_eff - 2. + 1. / mu_eff) / (n + 2.).mul_add(n + 2., mu_eff));
+        let damps = 2_f64.mul_add(0_f64.max(((mu_eff - 1.) / (n + 1.)).sqrt() - 1.), 1.) + cs;
+        let chi_n = n.sqrt() * (1. - 1. / (4. * n) + 1. / (21. * n * n));
+
+        Self {
+            mean: vec![0.; dimension],
+            sigma,
+            covariance: identity(dimension),
+            eigenvectors: identity(dimension),
+            scales: vec![1.; dimension],
+            path_sigma: vec![0.; dimension],
+            path_c: vec![0.; dimension],
+            generation: 0,
+            weights,
+            mu_eff,
+            cc,
+            cs,
+            c1,
+            cmu,
+            damps,
+            chi_n,
+        }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> Vec<f32> {
+        let scaled: Vec<f64> = self
+            .scales
+            .iter()
+            .map(|scale| scale * rng.sample::<f64, _>(StandardNormal))
+            .collect();
+
+        self.mean
+            .iter()
+            .zip(&self.eigenvectors)
+            .map(|(mean, row)| (self.sigma * dot(row, &scaled) + mean) as f32)
+            .collect()
+    }
+
+    pub fn update(&mut self, ranked: &[&[f32]]) {
+        let n = self.mean.len();
+        let old_mean = self.mean.clone();
+
+        // Steps of the selected agents from the old mean, in units of `sigma`.
+        let steps: Vec<Vec<f64>> = ranked
+            .iter()
+            .take(self.weights.len())
+            .map(|w| {
+                w.iter()
+                    .zip(&old_mean)
+                    .map(|(&x, mean)| (f64::from(x) - mean) / self.sigma)
+                    .collect()
+            })
+            .collect();
+        let weights = &self.weights[..steps.len()];
+
+        let mean_step: Vec<f64> = (0..n)
+            .map(|i| weights.iter().zip(&steps).map(|(w, y)| w * y[i]).sum())
+            .collect();
+        for (mean, step) in self.mean.iter_mut().zip(&mean_step) {
+            *mean += self.sigma * step;
+        }
+
+        // C^(-1/2) * mean_step, with C = B * D^2 * B^T.
+        let rotated: Vec<f64> = (0..n)
+            .map(|j| {
+                let column = self.eigenvectors.iter().map(|row| row[j]);
+                column.zip(&mean_step).map(|(b, y)| b * y).sum::<f64>() / self.scales[j]
+            })
+            .collect();
+        let whitened: Vec<f64> = self
+            .eigenvectors
+            .iter()
+            .map(|row| dot(row, &rotated))
+            .collect();
+
+        let sigma_learning = (self.cs * (2. - self.cs) * self.mu_eff).sqrt();
+        for (path, step) in self.path_sigma.iter_mut().zip(&whitened) {
+            *path = (1. - self.cs).mul_add(*path, sigma_learning * step);
+        }
+
+        self.generation += 1;
+        let ps_norm = dot(&self.path_sigma, &self.path_sigma).sqrt();
+        let decay = (1. - (1. - self.cs).powi(2 * self.generation as i32)).sqrt();
+        let h_sigma = ps_norm / decay / self.chi_n < 1.4 + 2. / (n as f64 + 1.);
+        let h_sigma = if h_sigma { 1. } else { 0. };
+
+        let covariance_learning = (self.cc * (2. - self.cc) * self.mu_eff).sqrt();
+        for (path, step) in self.path_c.iter_mut().zip(&mean_step) {
+            *path = (1. - self.cc).mul_add(*path, h_sigma * covariance_learning * step);
+        }
+
+        let stalled = (1. - h_sigma) * self.cc * (2. - self.cc);
+        for i in 0..n {
+            for j in 0..n {
+                let rank_mu: f64 = weights
+                    .iter()
+                    .zip(&steps)
+                    .map(|(w, y)| w * y[i] * y[j])
+                    .sum();
+                let rank_one =
+                    stalled.mul_add(self.covariance[i][j], self.path_c[i] * self.path_c[j]);
+                self.covariance[i][j] = (1. - self.c1 - self.cmu).mul_add(
+                    self.covariance[i][j],
+                    self.c1.mul_add(rank_one, self.cmu * rank_mu),
+                );
+            }
+        }
+
+        self.sigma *= ((self.cs / self.damps) * (ps_norm / self.chi_n - 1.)).exp();
+
+        let (eigenvalues, eigenvectors) = symmetric_eigen(self.covariance.clone());
+        self.scales = eigenvalues.iter().map(|v| v.max(1e-20).sqrt()).collect();
+        self.eigenvectors = eigenvectors;
+    }
+
+    #[must_use]
+    pub fn spread(&self) -> f64 {
+        let variances: Vec<f64> = (0..self.mean.len())
+            .map(|i| self.covariance[i][i])
+            .collect();
+        self.sigma * mean_sqrt(&variances)
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1. } else { 0. }).collect())
+        .collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn mean_sqrt(variances: &[f64]) -> f64 {
+    variances.iter().map(|v| v.sqrt()).sum::<f64>() / variances.len().max(1) as f64
+}
+
+/// Eigenvalues and eigenvectors (as columns) of a symmetric matrix, found with Jacobi rotations.
+fn symmetric_eigen(mut matrix: Vec<Vec<f64>>) -> (Vec<f64>, Vec<Vec<f64>>) {
+    const MAX_SWEEPS: usize = 50;
+    let n = matrix.len();
+    let mut vectors = identity(n);
+
+    // Rotate columns `p` and `q` of every row by the angle with `cos` and `sin`.
+    let rotate = |rows: &mut [Vec<f64>], p: usize, q: usize, cos: f64, sin: f64| {
+        for row in rows {
+            let (kp, kq) = (row[p], row[q]);
+            row[p] = cos.mul_add(kp, -sin * kq);
+            row[q] = sin.mul_add(kp, cos * kq);
+        }
+    };
+
+    for _ in 0..MAX_SWEEPS {
+        let diagonal: f64 = (0..n).map(|i| matrix[i][i] * matrix[i][i]).sum();
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| matrix[i][j] * matrix[i][j])
+            .sum();
+        if off_diagonal <= f64::EPSILON * f64::EPSILON * diagonal {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if matrix[p][q] == 0. {
+                    continue;
+                }
+
+                let theta = (matrix[q][q] - matrix[p][p]) / (2. * matrix[p][q]);
+                let tangent = theta.signum() / (theta.abs() + theta.hypot(1.));
+                let cos = 1. / tangent.hypot(1.);
+                let sin = tangent * cos;
+
+                rotate(&mut matrix, p, q, cos, sin);
+                let (upper, lower) = matrix.split_at_mut(q);
+                for (pk, qk) in upper[p].iter_mut().zip(&mut lower[0]) {
+                    (*pk, *qk) = (cos.mul_add(*pk, -sin * *qk), sin.mul_add(*pk, cos * *qk));
+                }
+                rotate(&mut vectors, p, q, cos, sin);
+            }
+        }
+    }
+
+    ((0..n).map(|i| matrix[i][i]).collect(), vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{symmetric_eigen, Optimizer, Strategy};
+
+    #[test]
+    fn test_parse_optimizer() {
+        assert_eq!("ga".parse::<Optimizer>().unwrap(), Optimizer::Genetic);
+        assert_eq!(
+            "cem".parse::<Optimizer>().unwrap(),
+            Optimizer::CrossEntropy {
+                elite_fraction: Optimizer::DEFAULT_ELITE_FRACTION,
+                noise: Optimizer::DEFAULT_NOISE
+            }
+        );
+        assert_eq!(
+            "cma-es:0.3".parse::<Optimizer>().unwrap(),
+            Optimizer::CmaEs { sigma: 0.3 }
+        );
+        let cem: Optimizer = "cem:0.2:1".parse().unwrap();
+        assert_eq!(cem.to_string().parse::<Optimizer>().unwrap(), cem);
+
+        assert!("cem:0".parse::<Optimizer>().is_err());
+        assert!("cma-es:0.3:1".parse::<Optimizer>().is_err());
+        assert!("pso".parse::<Optimizer>().is_err());
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let matrix = vec![vec![4., 1., 0.], vec![1., 3., 1.], vec![0., 1., 2.]];
+        let (values, vectors) = symmetric_eigen(matrix.clone());
+
+        for (k, value) in values.iter().enumerate() {
+            for (row, vector_i) in matrix.iter().zip(&vectors) {
+                let product: f64 = row.iter().zip(&vectors).map(|(a, v)| a * v[k]).sum();
+                assert!((product - value * vector_i[k]).abs() < 1e-9);
+            }
+        }
+    }
+
+    /// Run `optimizer` maximizing the negated squared distance from `target`.
+    fn optimize(optimizer: Optimizer, target: &[f32], generations: usize) -> Vec<f32> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut strategy = Strategy::new(optimizer, target.len(), 20).unwrap();
+
+        let fitness = |w: &[f32]| -> f32 {
+            -w.iter()
+                .zip(target)
+                .map(|(x, t)| (x - t).powi(2))
+                .sum::<f32>()
+        };
+        for _ in 0..generations {
+            let mut generation: Vec<Vec<f32>> =
+                (0..20).map(|_| strategy.sample(&mut rng)).collect();
+            generation.sort_by(|a, b| fitness(b).total_cmp(&fitness(a)));
+            let ranked: Vec<&[f32]> = generation.iter().map(Vec::as_slice).collect();
+            strategy.update(&ranked);
+        }
+
+        let mut best: Vec<Vec<f32>> = (0..20).map(|_| strategy.sample(&mut rng)).collect();
+        best.sort_by(|a, b| fitness(b).total_cmp(&fitness(a)));
+        assert!(strategy.spread() < 0.1, "spread {}", strategy.spread());
+        best.swap_remove(0)
+    }
+
+    #[test]
+    fn test_optimizers_find_optimum() {
+        let target = [0.5, -1.5, 2., 0.];
+
+        for optimizer in ["cem:0.25:0.01", "cma-es"] {
+            let best = optimize(optimizer.parse().unwrap(), &target, 150);
+            for (x, t) in best.iter().zip(&target) {
+                assert!((x - t).abs() < 0.1, "{optimizer}: {best:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use tetris_core::prelude::*;
+use tetris_heuristics::prelude::*;
+
+use crate::search::decide;
+use crate::{Budget, SearchCache, SearchConfig};
+
+/// Scores the state left after dropping a piece, lower is better.
+///
+/// Searches, MCTS and the UI only need an evaluator, [`crate::Agent`] is its linear implementation
+/// and [`crate::MlpEvaluator`] a neural network.
+pub trait Evaluator {
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore;
+
+    /// Score of `game`, which may reuse what this evaluator stored in `cache` before.
+    fn evaluate_cached(&self, game: &Game, _cache: &mut SearchCache) -> HeuristicScore {
+        self.evaluate(&EvalContext::from_game(game))
+    }
+}
+
+impl<E: Evaluator + ?Sized> Evaluator for &E {
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        (**self).evaluate(ctx)
+    }
+
+    fn evaluate_cached(&self, game: &Game, cache: &mut SearchCache) -> HeuristicScore {
+        (**self).evaluate_cached(game, cache)
+    }
+}
+
+impl<E: Evaluator + ?Sized> Evaluator for Box<E> {
+    fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+        (**self).evaluate(ctx)
+    }
+
+    fn evaluate_cached(&self, game: &Game, cache: &mut SearchCache) -> HeuristicScore {
+        (**self).evaluate_cached(game, cache)
+    }
+}
+
+/// Evaluator defined by a flat vector of parameters, which optimizers evolve.
+pub trait Parameterized: Evaluator {
+    fn params(&self) -> &[f32];
+
+    /// Evaluator like this one with other `params`, as many as it has.
+    #[must_use]
+    fn with_params(&self, params: Vec<f32>) -> Self
+    where
+        Self: Sized;
+}
+
+/// Chooses where the current piece of a game is dropped.
+pub trait Policy {
+    /// State after dropping the current piece of `game`, `None` if it can't be placed anywhere.
+    fn choose(&self, game: &Game) -> Option<Game>;
+
+    /// Play `game` until it's lost, or for at most `n_turns` pieces.
+    fn play(&self, game: Game, n_turns: Option<usize>) -> Game {
+        let mut game = game;
+        for _ in 0..n_turns.unwrap_or(usize::MAX) {
+            let Some(next_state) = self.choose(&game) else {
+                break;
+            };
+            game = next_state;
+            if game.is_lost() {
+                break;
+            }
+        }
+        game
+    }
+}
+
+/// Policy dropping pieces where `evaluator` likes the outcome of a search the most.
+#[derive(Debug, Clone)]
+pub struct SearchPolicy<E> {
+    pub evaluator: E,
+    pub search: SearchConfig,
+    pub budget: Budget,
+}
+
+impl<E> SearchPolicy<E> {
+    #[must_use]
+    pub const fn new(evaluator: E, search: SearchConfig) -> Self {
+        Self {
+            evaluator,
+            search,
+            budget: Budget::Unlimited,
+        }
+    }
+
+    #[must_use]
+    pub fn with_budget(self, budget: Budget) -> Self {
+        Self { budget, ..self }
+    }
+}
+
+impl<E: Evaluator> Policy for SearchPolicy<E> {
+    fn choose(&self, game: &Game) -> Option<Game> {
+        decide(
+            &self.evaluator,
+            game,
+            self.search,
+            self.budget,
+            &mut SearchCache::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::prelude::*;
+    use tetris_heuristics::prelude::*;
+
+    use super::{Evaluator, Policy, SearchPolicy};
+    use crate::test_utils::el_tetris_agent;
+    use crate::SearchConfig;
+
+    /// Hand-written bot keeping the board as low as possible.
+    struct LowestBoard;
+
+    impl Evaluator for LowestBoard {
+        fn evaluate(&self, ctx: &EvalContext) -> HeuristicScore {
+            ctx.board
+                .occupancy()
+                .iter()
+                .map(|column| (u32::BITS - column.leading_zeros()) as HeuristicScore)
+                .sum()
+        }
+    }
+
+    #[test]
+    fn test_search_policy_with_custom_evaluator() {
+        let policy = SearchPolicy::new(LowestBoard, SearchConfig::with_depth(2).with_beam_width(4));
+
+        let game = policy.play(Game::from_seed(0), Some(20));
+        assert_eq!(game.score.dropped_pieces, 20);
+        assert!(!game.is_lost());
+    }
+
+    #[test]
+    fn test_agent_policy_matches_agent_moves() {
+        let agent = el_tetris_agent(3, 0);
+        let search = SearchConfig::with_depth(2);
+
+        let played = SearchPolicy::new(&agent, search).play(agent.game.clone(), Some(10));
+        assert_eq!(
+            played,
+            agent.play_for_n_turns_or_lose(Some(10), search).game
+        );
+    }
+}
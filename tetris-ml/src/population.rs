@@ -1,70 +1,150 @@
-use std::ops::RangeInclusive;
-use std::sync::Arc;
-
 use anyhow::Result;
-use rand::{
-    distributions::WeightedIndex, prelude::Distribution, seq::SliceRandom, thread_rng, Rng,
-};
+use rand::distributions::{Distribution, Uniform};
+use rand::thread_rng;
 
 use rayon::prelude::*;
 use tetris_core::prelude::*;
 
 use crate::model_config::Config;
-use crate::{agent::Agent, BranchingMode};
+use crate::operators::normalize;
+use crate::{
+    Crossover, CrossoverOperator, MctsConfig, MctsPolicy, Mutation, MutationOperator,
+    Parameterized, Policy, SearchConfig, SearchPolicy, Selection, Strategy,
+};
 use indicatif::{ParallelProgressIterator, ProgressStyle};
 use rayon::iter::ParallelIterator;
 
+/// Evaluator evolved by a [`Population`], with the game it played when it was last evaluated.
+#[derive(Debug, Clone)]
+pub struct Entity<E> {
+    pub evaluator: E,
+    pub game: Game,
+}
+
+impl<E> Entity<E> {
+    #[must_use]
+    pub fn new(evaluator: E) -> Self {
+        Self {
+            evaluator,
+            game: Game::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn fitness(&self) -> f64 {
+        self.game.score.score as f64
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct Population {
-    pub entities: Vec<Agent>,
+pub struct Population<E> {
+    pub entities: Vec<Entity<E>>,
+    /// Strength of self-adaptive mutation of every entity, inherited by its offspring.
+    step_sizes: Vec<f32>,
     n_entities: usize,
     mutation_rate: f64,
     max_drops: Option<usize>,
+    search: SearchConfig,
+    mcts: Option<MctsConfig>,
+    /// Distribution new parameters are sampled from, crossover and mutation are used without it.
+    strategy: Option<Strategy>,
+    selection_method: Selection,
+    /// Best entities copied to the next generation unchanged by the genetic algorithm.
+    elite: usize,
+    crossover_method: Crossover,
+    mutation_method: Mutation,
+    normalize_weights: bool,
     evaluator: fn(&Self),
 }
 
-impl Population {
+impl<E: Parameterized + Clone + Send + Sync> Population<E> {
+    /// Population of evaluators like `template`, with random parameters.
+    ///
     /// # Errors
     ///
     /// This function will return an error if validation of [`Config`] fails.
     /// See [`Config::validate`] for more details.
-    pub fn new(config: &mut Config, evaluator: fn(&Self)) -> Result<Self> {
+    pub fn new(config: &Config, template: &E, evaluator: fn(&Self)) -> Result<Self> {
         config.validate()?;
 
-        let heuristics_ref = Arc::new(std::mem::take(&mut config.heuristics_used));
-
-        let entities: Vec<Agent> = (0..config.n_entities)
-            .map(|_| Agent::new(Arc::clone(&heuristics_ref)))
+        let mut rng = thread_rng();
+        let dimension = template.params().len();
+        let dist = Uniform::from(-1.0..1.0);
+        let mut entities: Vec<Entity<E>> = (0..config.n_entities)
+            .map(|_| {
+                let mut params: Vec<f32> = dist.sample_iter(&mut rng).take(dimension).collect();
+                if config.normalize_weights {
+                    normalize(&mut params);
+                }
+                Entity::new(template.with_params(params))
+            })
             .collect();
 
+        let strategy = Strategy::new(config.optimizer, dimension, config.n_entities);
+        if let Some(strategy) = &strategy {
+            for entity in &mut entities {
+                entity.evaluator = entity.evaluator.with_params(strategy.sample(&mut rng));
+            }
+        }
+
         Ok(Self {
             entities,
+            step_sizes: vec![config.mutation.initial_step_size(); config.n_entities],
             mutation_rate: config.mutation_rate,
             max_drops: config.max_drops,
+            search: config.search,
+            mcts: config.mcts,
+            strategy,
+            selection_method: config.selection,
+            elite: config.elite,
+            crossover_method: config.crossover,
+            mutation_method: config.mutation,
+            normalize_weights: config.normalize_weights,
             n_entities: config.n_entities,
             evaluator,
         })
     }
 
+    /// Next generation bred from this one, whose fitness has to be known,
+    /// see [`Population::evaluate`]. New entities haven't played their games yet.
     #[must_use]
     pub fn advance_population(&self) -> Self {
-        const WEIGHT_RANGE: RangeInclusive<f32> = -1.0..=1.0;
+        if self.strategy.is_some() {
+            return self.clone().resample();
+        }
+
+        let elites: Vec<(Entity<E>, f32)> = self
+            .ranking()
+            .into_iter()
+            .take(self.elite)
+            .map(|idx| (self.entities[idx].clone(), self.step_sizes[idx]))
+            .collect();
+
         self.clone()
-            .evaluate()
             .selection()
             .crossover()
-            .mutation(WEIGHT_RANGE)
+            .mutation()
+            .with_elites(elites)
+    }
+
+    /// Mean standard deviation of the distribution new parameters are sampled from,
+    /// `None` for the genetic algorithm.
+    #[must_use]
+    pub fn distribution_spread(&self) -> Option<f64> {
+        self.strategy.as_ref().map(Strategy::spread)
     }
 
-    fn evaluate(self) -> Self {
-        self.restart_games().finish_all_games()
+    /// This generation after every entity played a new game, which determines its fitness.
+    #[must_use]
+    pub fn evaluate(&self) -> Self {
+        self.clone().restart_games().finish_all_games()
     }
 
     #[must_use]
     fn restart_games(mut self) -> Self {
         self.entities
             .par_iter_mut()
-            .for_each(|x: &mut Agent| x.game = Game::new());
+            .for_each(|x: &mut Entity<E>| x.game = Game::new());
         self
     }
 
@@ -73,14 +153,22 @@ impl Population {
         let completed_population = self
             .entities
             .into_par_iter()
-            .map(|entity| entity.play_for_n_turns_or_lose(self.max_drops, BranchingMode::default()))
+            .map(|mut entity| {
+                let game = entity.game.clone();
+                let evaluator = &entity.evaluator;
+                entity.game = match self.mcts {
+                    Some(mcts) => MctsPolicy::new(evaluator, mcts).play(game, self.max_drops),
+                    None => SearchPolicy::new(evaluator, self.search).play(game, self.max_drops),
+                };
+                entity
+            })
             .progress_with_style(
                 ProgressStyle::with_template(
                     "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
                 )
                 .unwrap(),
             )
-            .collect::<Vec<Agent>>();
+            .collect::<Vec<Entity<E>>>();
 
         let finalized_population = Self {
             entities: completed_population,
@@ -92,40 +180,52 @@ impl Population {
         finalized_population
     }
 
+    /// Fit the strategy's distribution to the evaluated entities and sample a new generation from it.
     #[must_use]
-    // Rulette selection
-    fn selection(self) -> Self {
-        let probs: Vec<f64> = self
-            .entities
-            .iter()
-            .map(Agent::fitness)
-            .map(|fitness| fitness + 1.) // zabezpieczenie przed sytuacją, gdyby wszystkie fitness score były równe 0
+    fn resample(self) -> Self {
+        let mut strategy = self.strategy.clone().expect("Only strategies resample.");
+        let ranked: Vec<&[f32]> = self
+            .sorted_by_performance()
+            .into_iter()
+            .map(|entity| entity.evaluator.params())
             .collect();
+        strategy.update(&ranked);
 
-        let dist = WeightedIndex::new(probs).unwrap();
-
-        let rng = thread_rng();
-
-        let new_population = dist
-            .sample_iter(rng)
-            .take(self.n_entities)
-            .map(|idx| self.entities[idx].clone())
+        let mut rng = thread_rng();
+        let template = &self.entities[0];
+        let entities = (0..self.n_entities)
+            .map(|_| Entity::new(template.evaluator.with_params(strategy.sample(&mut rng))))
             .collect();
 
         Self {
-            entities: new_population,
+            entities,
+            strategy: Some(strategy),
             ..self
         }
     }
 
+    /// Parents of the entities which aren't elites, an even number of them for the crossover.
     #[must_use]
-    fn crossover(self) -> Self {
-        let cross_method = |w1: f32, w2: f32| -> f32 {
-            let alpha: f32 = rand::thread_rng().gen_range(0.0..1.0);
+    fn selection(self) -> Self {
+        let fitness: Vec<f64> = self.entities.iter().map(Entity::fitness).collect();
+        let n_parents = (self.n_entities - self.elite).next_multiple_of(2);
 
-            alpha.mul_add(w1, (1.0 - alpha) * w2)
-        };
+        let parents = self
+            .selection_method
+            .select(&fitness, n_parents, &mut thread_rng());
+
+        Self {
+            entities: parents
+                .iter()
+                .map(|&idx| self.entities[idx].clone())
+                .collect(),
+            step_sizes: parents.iter().map(|&idx| self.step_sizes[idx]).collect(),
+            ..self
+        }
+    }
 
+    #[must_use]
+    fn crossover(self) -> Self {
         let offsprings = self
             .entities
             .into_par_iter()
@@ -133,31 +233,18 @@ impl Population {
             .flat_map(|entities| {
                 let first = &entities[0];
                 let second = &entities[1];
+                let (first_params, second_params) = self.crossover_method.cross(
+                    first.evaluator.params(),
+                    second.evaluator.params(),
+                    &mut thread_rng(),
+                );
 
                 vec![
-                    Agent::from_weights(
-                        first
-                            .weights
-                            .iter()
-                            .zip(second.weights.iter())
-                            .map(|(&first_w, &second_w)| cross_method(first_w, second_w))
-                            .collect(),
-                        &first.heuristics,
-                    )
-                    .unwrap(),
-                    Agent::from_weights(
-                        first
-                            .weights
-                            .iter()
-                            .zip(second.weights.iter())
-                            .map(|(&first_w, &second_w)| cross_method(second_w, first_w))
-                            .collect(),
-                        &second.heuristics,
-                    )
-                    .unwrap(),
+                    Entity::new(first.evaluator.with_params(first_params)),
+                    Entity::new(second.evaluator.with_params(second_params)),
                 ]
             })
-            .collect::<Vec<Agent>>();
+            .collect::<Vec<Entity<E>>>();
 
         Self {
             entities: offsprings,
@@ -166,38 +253,64 @@ impl Population {
     }
 
     #[must_use]
-    fn mutation(self, weights_sampling_interval: RangeInclusive<f32>) -> Self {
+    fn mutation(self) -> Self {
         let mut rng = thread_rng();
 
-        let new_population = self
-            .entities
-            .into_iter()
-            .map(|mut entity| {
-                if rng.gen_bool(self.mutation_rate) {
-                    if let Some(random_weight) = entity.weights.choose_mut(&mut rng) {
-                        *random_weight += rng.gen_range(weights_sampling_interval.clone());
-                    }
-                }
-                entity
-            })
-            .collect();
+        let mut entities = self.entities;
+        let mut step_sizes = self.step_sizes;
+        for (entity, step_size) in entities.iter_mut().zip(&mut step_sizes) {
+            let mut params = entity.evaluator.params().to_vec();
+            self.mutation_method
+                .mutate(&mut params, step_size, self.mutation_rate, &mut rng);
+            if self.normalize_weights {
+                normalize(&mut params);
+            }
+            entity.evaluator = entity.evaluator.with_params(params);
+        }
 
         Self {
-            entities: new_population,
+            entities,
+            step_sizes,
             ..self
         }
     }
 
+    /// Put `elites` with their step sizes in front of the offspring,
+    /// keeping the size of the population.
+    #[must_use]
+    fn with_elites(mut self, elites: Vec<(Entity<E>, f32)>) -> Self {
+        let n_offspring = self.n_entities - elites.len();
+        self.entities.truncate(n_offspring);
+        self.step_sizes.truncate(n_offspring);
+
+        let (elites, step_sizes): (Vec<_>, Vec<_>) = elites.into_iter().unzip();
+        self.entities.splice(0..0, elites);
+        self.step_sizes.splice(0..0, step_sizes);
+        self
+    }
+
+    /// Indices of entities from the best to the worst one.
     #[must_use]
-    fn sorted_by_performance(&self) -> Vec<&Agent> {
-        let mut entity_refs = self.entities.iter().collect::<Vec<_>>();
+    fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.entities.len()).collect();
+        ranking.sort_unstable_by(|&x, &y| {
+            self.entities[y]
+                .fitness()
+                .total_cmp(&self.entities[x].fitness())
+        });
+        ranking
+    }
 
-        entity_refs.sort_unstable_by(|x, y| y.fitness().total_cmp(&x.fitness()));
-        entity_refs
+    #[must_use]
+    fn sorted_by_performance(&self) -> Vec<&Entity<E>> {
+        self.ranking()
+            .into_iter()
+            .map(|idx| &self.entities[idx])
+            .collect()
     }
 
     #[must_use]
-    pub fn get_best_entity(&self) -> &Agent {
+    pub fn get_best_entity(&self) -> &Entity<E> {
         self.sorted_by_performance()
             .first()
             .expect("Population cannot be empty.")
@@ -205,7 +318,7 @@ impl Population {
 
     #[must_use]
     pub fn mean_fitness(&self) -> f64 {
-        self.entities.iter().map(Agent::fitness).sum::<f64>() / self.entities.len() as f64
+        self.entities.iter().map(Entity::fitness).sum::<f64>() / self.entities.len() as f64
     }
 
     #[must_use]
@@ -235,8 +348,73 @@ impl Population {
             .iter()
             .skip(self.entities.len() / 2)
             .take(take_n)
-            .map(Agent::fitness)
+            .map(Entity::fitness)
             .sum::<f64>()
             / take_n as f64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::Population;
+    use crate::test_utils::training_config;
+    use crate::{Agent, Config, Crossover, Mutation, Optimizer, Parameterized, Selection};
+
+    #[test]
+    fn test_generations_keep_size_and_dimension() {
+        let cma_es = Config {
+            optimizer: Optimizer::CmaEs { sigma: 0.5 },
+            elite: 0,
+            mutation: Mutation::default(),
+            ..training_config()
+        };
+        for config in [training_config(), cma_es] {
+            let template = Agent::new(Arc::new(config.heuristics_used.clone()));
+
+            let population = Population::new(&config, &template, |_| {}).unwrap();
+            let next = population.evaluate().advance_population();
+
+            assert_eq!(next.entities.len(), 6);
+            assert_eq!(next.step_sizes.len(), 6);
+            assert!(next
+                .entities
+                .iter()
+                .all(|entity| entity.evaluator.params().len() == 2));
+        }
+    }
+
+    #[test]
+    fn test_strategy_rejects_genetic_operators() {
+        let cem = Config {
+            optimizer: Optimizer::CrossEntropy {
+                elite_fraction: 0.5,
+                noise: 0.1,
+            },
+            ..training_config()
+        };
+        assert!(cem.validate().is_err());
+
+        let plain = Config {
+            selection: Selection::default(),
+            elite: 0,
+            crossover: Crossover::default(),
+            mutation: Mutation::default(),
+            ..cem
+        };
+        assert!(plain.validate().is_ok());
+        assert!(Config {
+            normalize_weights: true,
+            ..plain.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(Config {
+            selection: Selection::Rank,
+            ..plain
+        }
+        .validate()
+        .is_err());
+    }
+}
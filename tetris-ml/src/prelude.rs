@@ -1,5 +1,19 @@
 pub use crate::agent::Agent;
-pub use crate::branching_modes::BranchingMode;
+pub use crate::budget::{Budget, SearchLimit};
+pub use crate::cache::{CacheStats, SearchCache};
+#[cfg(feature = "training")]
 pub use crate::genetic_algorithm::GA;
+pub use crate::mcts::{mcts_search, MctsConfig, MctsPolicy};
+pub use crate::mlp::{MlpConfig, MlpEvaluator, MlpInput};
+pub use crate::model::SavedModel;
 pub use crate::model_config::Config;
-pub use crate::population::Population;
+pub use crate::normalization::{
+    sample_states, sample_states_with, Normalization, NormalizationMode, Normalizer,
+};
+pub use crate::operators::{normalize, Crossover, CrossoverOperator, Mutation, MutationOperator};
+pub use crate::optimizer::{Optimizer, Strategy};
+pub use crate::policy::{Evaluator, Parameterized, Policy, SearchPolicy};
+#[cfg(feature = "training")]
+pub use crate::population::{Entity, Population};
+pub use crate::search::{best_state, decide, SearchConfig, UnseenPieces};
+pub use crate::selection::Selection;
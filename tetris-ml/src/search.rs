@@ -0,0 +1,617 @@
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use rand::seq::SliceRandom;
+use tetris_core::prelude::*;
+use tetris_core::randomizer::Randomizer;
+use tetris_heuristics::HeuristicScore;
+
+use crate::budget::SearchLimit;
+use crate::cache::{transposition_key, BoardKey, SearchCache};
+use crate::{Agent, Budget, Evaluator};
+
+/// The current piece and the one shown in the preview.
+pub(crate) const KNOWN_PIECES: usize = 2;
+
+/// How plies past the known pieces choose the piece to drop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnseenPieces {
+    /// There are no such plies, only the known pieces are searched.
+    #[default]
+    NotSearched,
+    /// Cheat by using the pieces the game is going to deal, as if the preview queue was longer.
+    Peek,
+    /// Average the best outcome for every piece type, which are all equally likely.
+    Expectimax,
+    /// Like [`UnseenPieces::Expectimax`], but only over this many randomly chosen piece types.
+    Sampled(usize),
+}
+
+/// How far ahead an agent looks before choosing where to drop the current piece.
+///
+/// Every ply drops one piece, the first one being the current piece and the second one
+/// the piece shown in the preview. Pieces of deeper plies are chosen by `unseen_pieces`,
+/// without it the search never goes deeper than the known pieces.
+/// Only the boards left after the last ply are compared,
+/// intermediate plies are pruned to the `beam_width` best boards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// Number of pieces dropped along every searched path, at least 1.
+    pub depth: usize,
+    /// How many best boards are expanded after each ply, all of them if `None`.
+    pub beam_width: Option<usize>,
+    pub unseen_pieces: UnseenPieces,
+}
+
+impl Default for SearchConfig {
+    /// Only consider the current piece.
+    fn default() -> Self {
+        Self::GREEDY
+    }
+}
+
+impl SearchConfig {
+    pub const GREEDY: Self = Self::with_depth(1);
+
+    /// Exhaustive search over `depth` pieces.
+    #[must_use]
+    pub const fn with_depth(depth: usize) -> Self {
+        Self {
+            depth,
+            beam_width: None,
+            unseen_pieces: UnseenPieces::NotSearched,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_beam_width(self, beam_width: usize) -> Self {
+        Self {
+            beam_width: Some(beam_width),
+            ..self
+        }
+    }
+
+    /// Search past the preview with the pieces the game is going to deal,
+    /// which a player can't know.
+    #[must_use]
+    pub const fn peek(self) -> Self {
+        Self {
+            unseen_pieces: UnseenPieces::Peek,
+            ..self
+        }
+    }
+
+    /// Average over the pieces that can follow the preview.
+    #[must_use]
+    pub const fn expectimax(self) -> Self {
+        Self {
+            unseen_pieces: UnseenPieces::Expectimax,
+            ..self
+        }
+    }
+
+    /// Like [`SearchConfig::expectimax`], but only over `samples` piece types at every chance node.
+    #[must_use]
+    pub const fn sampled_expectimax(self, samples: usize) -> Self {
+        Self {
+            unseen_pieces: UnseenPieces::Sampled(samples),
+            ..self
+        }
+    }
+
+    /// Number of plies which are actually searched, `depth` capped at the known pieces
+    /// if the unseen ones aren't searched.
+    #[must_use]
+    pub fn searched_depth(&self) -> usize {
+        match self.unseen_pieces {
+            UnseenPieces::NotSearched => self.depth.min(KNOWN_PIECES),
+            _ => self.depth,
+        }
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if `depth`, the beam width or the number of samples
+    /// is 0, or `depth` is bigger than the number of known pieces while unseen pieces aren't searched.
+    pub fn validate(&self) -> Result<()> {
+        if self.depth == 0 {
+            bail!("Search depth must be at least 1.");
+        }
+        if self.beam_width == Some(0) {
+            bail!("Beam width must be at least 1.");
+        }
+        if self.unseen_pieces == UnseenPieces::Sampled(0) {
+            bail!("Expectimax must sample at least 1 piece type.");
+        }
+        if self.depth > self.searched_depth() {
+            bail!(
+                "Only {KNOWN_PIECES} pieces are known, add expectimax or peek to search {} plies.",
+                self.depth
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Display for SearchConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "N={}", self.depth)?;
+        if let Some(width) = self.beam_width {
+            write!(f, " K={width}")?;
+        }
+        match self.unseen_pieces {
+            UnseenPieces::NotSearched => Ok(()),
+            UnseenPieces::Peek => write!(f, " peek"),
+            UnseenPieces::Expectimax => write!(f, " E"),
+            UnseenPieces::Sampled(samples) => write!(f, " E={samples}"),
+        }
+    }
+}
+
+impl FromStr for SearchConfig {
+    type Err = anyhow::Error;
+
+    /// Parse `depth` followed by `:`-separated options: a beam width, `expectimax`,
+    /// `expectimax=samples` or `peek`, e.g. `2:8` or `3:8:expectimax=3`.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(':');
+
+        let depth = parts.next().unwrap_or_default();
+        let depth: usize = depth
+            .parse()
+            .with_context(|| format!("Invalid search depth {depth:?}."))?;
+
+        let mut search = Self::with_depth(depth);
+        for part in parts {
+            search = match part.split_once('=') {
+                None if part == "expectimax" => search.expectimax(),
+                None if part == "peek" => search.peek(),
+                Some(("expectimax", samples)) => match samples.parse() {
+                    Ok(samples) if samples > 0 => search.sampled_expectimax(samples),
+                    _ => {
+                        bail!("Invalid number of samples {samples:?}, expected a positive number.")
+                    }
+                },
+                _ => match part.parse() {
+                    Ok(width) if width > 0 => search.with_beam_width(width),
+                    _ => bail!("Invalid beam width {part:?}, expected a positive number."),
+                },
+            };
+        }
+
+        search.validate()?;
+        Ok(search)
+    }
+}
+
+/// Board reached after some plies, remembering which first move led to it.
+struct Node {
+    first_move: usize,
+    game: Game,
+    score: HeuristicScore,
+}
+
+/// Best state after dropping the current piece of `game`, chosen with the search `config` asks for.
+/// Search gives up with `None` once `limit` is reached.
+#[must_use]
+pub fn best_state<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    game: &Game,
+    config: SearchConfig,
+    cache: &mut SearchCache,
+    limit: &SearchLimit,
+) -> Option<Game> {
+    match config.unseen_pieces {
+        UnseenPieces::NotSearched => {
+            let known = SearchConfig {
+                depth: config.searched_depth(),
+                ..config
+            };
+            beam_search(evaluator, game, known, cache, limit)
+        }
+        UnseenPieces::Peek => beam_search(evaluator, game, config, cache, limit),
+        UnseenPieces::Expectimax | UnseenPieces::Sampled(_) => {
+            expectimax(evaluator, game, config, cache, limit)
+        }
+    }
+}
+
+/// Best state found within `budget` by iterative deepening.
+///
+/// Searches of increasing depth run up to `config.depth`,
+/// and the result of the deepest one that finished in time is kept.
+/// Greedy move is always found, even if the budget is already exhausted.
+#[must_use]
+pub fn decide<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    game: &Game,
+    config: SearchConfig,
+    budget: Budget,
+    cache: &mut SearchCache,
+) -> Option<Game> {
+    if budget == Budget::Unlimited {
+        return best_state(evaluator, game, config, cache, &SearchLimit::NONE);
+    }
+
+    let limit = budget.start(cache);
+    let shallowest = SearchConfig { depth: 1, ..config };
+    let mut best = best_state(evaluator, game, shallowest, cache, &SearchLimit::NONE)?;
+
+    for depth in 2..=config.searched_depth() {
+        let deeper = best_state(
+            evaluator,
+            game,
+            SearchConfig { depth, ..config },
+            cache,
+            &limit,
+        );
+        match deeper {
+            Some(deeper) if !limit.is_reached(cache) => best = deeper,
+            _ => break,
+        }
+    }
+
+    Some(best)
+}
+
+/// Best state after dropping the current piece of `game`, according to `evaluator`
+/// looking `config.depth` plies ahead. Plies past the preview peek at the pieces
+/// the game is going to deal.
+///
+/// Paths which lose before the last ply are dropped, unless every path does -
+/// then the best of the deepest boards that were reached decides.
+/// States reached by several paths are only expanded once, from the best scored path.
+/// Search gives up with `None` once `limit` is reached.
+#[must_use]
+pub fn beam_search<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    game: &Game,
+    config: SearchConfig,
+    cache: &mut SearchCache,
+    limit: &SearchLimit,
+) -> Option<Game> {
+    let first_moves = Agent::get_all_possible_next_game_states(game);
+
+    let mut frontier: Vec<Node> = first_moves
+        .iter()
+        .enumerate()
+        .map(|(first_move, game)| Node {
+            first_move,
+            score: evaluator.evaluate_cached(game, cache),
+            game: game.clone(),
+        })
+        .collect();
+
+    for ply in 1..config.depth {
+        if let Some(width) = config.beam_width {
+            // Stable sort keeps the order of discovery between equally scored boards.
+            frontier.sort_by(|a, b| a.score.total_cmp(&b.score));
+            frontier.truncate(width);
+        }
+
+        let mut next_frontier: Vec<Node> = Vec::new();
+        let mut transpositions = HashMap::new();
+        for node in &frontier {
+            if limit.is_reached(cache) {
+                return None;
+            }
+            for game in Agent::get_all_possible_next_game_states(&node.game) {
+                let next = Node {
+                    first_move: node.first_move,
+                    score: evaluator.evaluate_cached(&game, cache),
+                    game,
+                };
+
+                let entry = transpositions.entry(transposition_key(&next.game, ply + 1));
+                cache.record_transposition(matches!(entry, Entry::Occupied(_)));
+                match entry {
+                    Entry::Occupied(seen) => {
+                        let seen: &mut Node = &mut next_frontier[*seen.get()];
+                        if next.score < seen.score {
+                            *seen = next;
+                        }
+                    }
+                    Entry::Vacant(slot) => {
+                        slot.insert(next_frontier.len());
+                        next_frontier.push(next);
+                    }
+                }
+            }
+        }
+
+        if next_frontier.is_empty() {
+            break;
+        }
+        frontier = next_frontier;
+    }
+
+    let best = frontier
+        .into_iter()
+        .min_by(|a, b| a.score.total_cmp(&b.score))?;
+
+    first_moves.into_iter().nth(best.first_move)
+}
+
+/// Best state after dropping the current piece, minimizing the expected score of the boards
+/// left after `config.depth` plies, when pieces past the preview aren't known.
+///
+/// Dropping a piece is a min node over its placements (the `beam_width` best ones by their own
+/// score when set), choosing the piece past the preview is a chance node averaging over piece types.
+/// Piece without any placement tops out the game, so its outcome is infinitely bad.
+/// Expected values of states reached by several paths are only computed once.
+/// Search gives up with `None` once `limit` is reached.
+#[must_use]
+pub fn expectimax<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    game: &Game,
+    config: SearchConfig,
+    cache: &mut SearchCache,
+    limit: &SearchLimit,
+) -> Option<Game> {
+    let mut search = Expectimax {
+        evaluator,
+        config,
+        cache,
+        limit,
+        transpositions: HashMap::new(),
+    };
+    let mut game = game.clone();
+    let ((best, rotated), _) = search.best_placement(&mut game, 1)?;
+    if limit.is_reached(search.cache) {
+        return None;
+    }
+    game.make_move(best, rotated);
+    Some(game)
+}
+
+/// Depth-first search making and unmaking moves on a single game, instead of copying it.
+struct Expectimax<'a, E: ?Sized> {
+    evaluator: &'a E,
+    config: SearchConfig,
+    cache: &'a mut SearchCache,
+    limit: &'a SearchLimit,
+    transpositions: HashMap<(BoardKey, Option<PieceType>, usize), HeuristicScore>,
+}
+
+impl<E: Evaluator + ?Sized> Expectimax<'_, E> {
+    /// Placement of the game's current piece minimizing the expected value, with that value.
+    /// `game` is left as it was.
+    fn best_placement(
+        &mut self,
+        game: &mut Game,
+        ply: usize,
+    ) -> Option<((Piece, bool), HeuristicScore)> {
+        if self.limit.is_reached(self.cache) {
+            return None;
+        }
+
+        let mut children = Vec::new();
+        for placement @ (piece, rotated) in Agent::get_all_possible_placements(game) {
+            let undo = game.make_move(piece, rotated);
+            if !game.is_lost() {
+                children.push((placement, self.evaluator.evaluate_cached(game, self.cache)));
+            }
+            game.unmake_move(undo);
+        }
+
+        if ply < self.config.depth {
+            if let Some(width) = self.config.beam_width {
+                children.sort_by(|a, b| a.1.total_cmp(&b.1));
+                children.truncate(width);
+            }
+            for &mut ((piece, rotated), ref mut score) in &mut children {
+                let undo = game.make_move(piece, rotated);
+                *score = self.expected_value(game, ply);
+                game.unmake_move(undo);
+            }
+        }
+
+        children.into_iter().min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Value of `game` after `ply` pieces were dropped, averaged over the pieces that may come next.
+    fn expected_value(&mut self, game: &mut Game, ply: usize) -> HeuristicScore {
+        // Past the preview the game's piece comes from its randomizer, which isn't searched.
+        let piece = (ply < KNOWN_PIECES).then_some(game.piece.block_type);
+        let key = (game.board.occupancy(), piece, ply);
+        let known = self.transpositions.get(&key).copied();
+        self.cache.record_transposition(known.is_some());
+        if let Some(value) = known {
+            return value;
+        }
+
+        let piece_types = match self.config.unseen_pieces {
+            _ if ply < KNOWN_PIECES => vec![game.piece.block_type],
+            UnseenPieces::NotSearched | UnseenPieces::Peek => vec![game.piece.block_type],
+            UnseenPieces::Expectimax => PieceType::ALL.to_vec(),
+            UnseenPieces::Sampled(samples) => {
+                // Seeded by the board, so that the same decision is made for the same game.
+                let mut hasher = DefaultHasher::new();
+                game.board.hash(&mut hasher);
+                let mut rng = Randomizer::from_seed(hasher.finish());
+                PieceType::ALL
+                    .choose_multiple(&mut rng, samples)
+                    .copied()
+                    .collect()
+            }
+        };
+
+        let current = game.piece;
+        let mut total = 0.;
+        for &block_type in &piece_types {
+            game.piece = Piece::new(block_type);
+            total += self
+                .best_placement(game, ply + 1)
+                .map_or(HeuristicScore::INFINITY, |(_, value)| value);
+        }
+        game.piece = current;
+
+        let value = total / piece_types.len() as HeuristicScore;
+        self.transpositions.insert(key, value);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris_core::randomizer::Randomizer;
+
+    use super::{beam_search, SearchConfig, UnseenPieces};
+    use crate::test_utils::el_tetris_agent;
+    use crate::{Agent, SearchCache, SearchLimit};
+
+    #[test]
+    fn test_parse_search_config() {
+        assert_eq!("1".parse::<SearchConfig>().unwrap(), SearchConfig::GREEDY);
+        assert_eq!(
+            "2:8".parse::<SearchConfig>().unwrap(),
+            SearchConfig::with_depth(2).with_beam_width(8)
+        );
+        assert_eq!(
+            "3:8:peek".parse::<SearchConfig>().unwrap(),
+            SearchConfig::with_depth(3).with_beam_width(8).peek()
+        );
+        assert_eq!(
+            "3:8:expectimax=3".parse::<SearchConfig>().unwrap(),
+            SearchConfig::with_depth(3)
+                .with_beam_width(8)
+                .sampled_expectimax(3)
+        );
+        assert_eq!(
+            "3:expectimax"
+                .parse::<SearchConfig>()
+                .unwrap()
+                .unseen_pieces,
+            UnseenPieces::Expectimax
+        );
+        assert!("0".parse::<SearchConfig>().is_err());
+        assert!("3:8".parse::<SearchConfig>().is_err());
+        assert!("2:expectimax=0".parse::<SearchConfig>().is_err());
+        assert!("2:0".parse::<SearchConfig>().is_err());
+        assert!("two".parse::<SearchConfig>().is_err());
+
+        assert!(SearchConfig::with_depth(2)
+            .with_beam_width(0)
+            .validate()
+            .is_err());
+        assert!(SearchConfig::with_depth(3)
+            .sampled_expectimax(0)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_greedy_search_picks_best_next_state() {
+        let agent = el_tetris_agent(3, 0);
+
+        let best = Agent::get_all_possible_next_game_states(&agent.game)
+            .into_iter()
+            .min_by(|a, b| {
+                agent
+                    .forward_with_game(a)
+                    .total_cmp(&agent.forward_with_game(b))
+            });
+
+        let mut cache = SearchCache::default();
+        let chosen = beam_search(
+            &agent,
+            &agent.game,
+            SearchConfig::GREEDY,
+            &mut cache,
+            &SearchLimit::NONE,
+        );
+        assert_eq!(chosen, best);
+    }
+
+    #[test]
+    fn test_wide_beam_matches_exhaustive_search() {
+        let agent = el_tetris_agent(5, 10);
+
+        let exhaustive = agent.next_best_state(SearchConfig::with_depth(2));
+        let wide_beam = agent.next_best_state(SearchConfig::with_depth(2).with_beam_width(1000));
+        assert_eq!(exhaustive, wide_beam);
+        assert!(exhaustive.is_some());
+    }
+
+    #[test]
+    fn test_cached_search_matches_uncached_scores() {
+        let agent = el_tetris_agent(4, 10);
+
+        let mut cache = SearchCache::default();
+        let search = SearchConfig::with_depth(3).with_beam_width(20).peek();
+        let chosen =
+            beam_search(&agent, &agent.game, search, &mut cache, &SearchLimit::NONE).unwrap();
+
+        // Different orders of the same pieces often lead to the same board.
+        assert!(cache.transposition_stats.hits > 0);
+        assert!(cache.evaluation_stats.hits > 0);
+        assert!(
+            (agent.forward_cached(&chosen, &mut cache) - agent.forward_with_game(&chosen)).abs()
+                < f32::EPSILON
+        );
+    }
+
+    #[test]
+    fn test_beam_search_survives() {
+        let agent = el_tetris_agent(0, 0);
+
+        let agent = agent
+            .play_for_n_turns_or_lose(Some(50), SearchConfig::with_depth(2).with_beam_width(4));
+        assert!(!agent.game.is_lost());
+        assert_eq!(agent.game.score.dropped_pieces, 50);
+    }
+
+    #[test]
+    fn test_expectimax_over_known_pieces_matches_exhaustive_search() {
+        let agent = el_tetris_agent(7, 10);
+
+        let search = SearchConfig::with_depth(2);
+        assert_eq!(
+            agent.next_best_state(search.expectimax()),
+            agent.next_best_state(search)
+        );
+    }
+
+    #[test]
+    fn test_default_search_stops_at_known_pieces() {
+        let agent = el_tetris_agent(1, 5);
+
+        let deep = SearchConfig::with_depth(4);
+        assert!(deep.validate().is_err());
+        assert_eq!(deep.searched_depth(), 2);
+        assert_eq!(
+            agent.next_best_state(deep),
+            agent.next_best_state(SearchConfig::with_depth(2))
+        );
+    }
+
+    #[test]
+    fn test_expectimax_doesnt_peek_at_unseen_pieces() {
+        let agent = el_tetris_agent(1, 5);
+
+        // Same visible state, but a different sequence of pieces past the preview.
+        let mut other = agent.clone();
+        other.game.randomizer = Randomizer::from_seed(1234);
+
+        let search = SearchConfig::with_depth(3).with_beam_width(3).expectimax();
+        let chosen = agent.next_best_state(search).unwrap();
+        let other_chosen = other.next_best_state(search).unwrap();
+        assert_eq!(chosen.board, other_chosen.board);
+    }
+
+    #[test]
+    fn test_sampled_expectimax_survives() {
+        let agent = el_tetris_agent(2, 0);
+
+        let search = SearchConfig::with_depth(3)
+            .with_beam_width(3)
+            .sampled_expectimax(2);
+        let agent = agent.play_for_n_turns_or_lose(Some(30), search);
+        assert!(!agent.game.is_lost());
+        assert_eq!(agent.game.score.dropped_pieces, 30);
+    }
+}
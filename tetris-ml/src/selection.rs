@@ -0,0 +1,195 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::Rng;
+
+/// How parents of the next generation are drawn from the evaluated one.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Selection {
+    /// Probability proportional to fitness (plus one, so that agents without points can be drawn).
+    #[default]
+    Roulette,
+    /// Best of `k` agents drawn uniformly.
+    Tournament { k: usize },
+    /// Probability proportional to the position in the ranking, the worst agent being first.
+    Rank,
+    /// Uniformly from the best `fraction` of agents.
+    Truncation { fraction: f64 },
+}
+
+impl Selection {
+    pub const DEFAULT_TOURNAMENT_SIZE: usize = 3;
+    pub const DEFAULT_TRUNCATION_FRACTION: f64 = 0.5;
+
+    /// # Errors
+    ///
+    /// This function will return an error if the tournament is empty,
+    /// or the truncation fraction isn't in `(0, 1]`.
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            Self::Roulette | Self::Rank => {}
+            Self::Tournament { k } => {
+                if k == 0 {
+                    bail!("Tournament size must be at least 1.")
+                }
+            }
+            Self::Truncation { fraction } => {
+                if !(fraction > 0. && fraction <= 1.) {
+                    bail!("Truncation fraction must be in (0, 1], got {fraction}.")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Indices of `n` agents with the given `fitness` chosen to be parents, possibly repeated.
+    pub fn select(&self, fitness: &[f64], n: usize, rng: &mut impl Rng) -> Vec<usize> {
+        match *self {
+            Self::Roulette => {
+                let weights = fitness.iter().map(|fitness| fitness + 1.);
+                let dist = WeightedIndex::new(weights).expect("Fitness is never negative.");
+                dist.sample_iter(rng).take(n).collect()
+            }
+            Self::Tournament { k } => (0..n)
+                .map(|_| {
+                    (0..k)
+                        .map(|_| rng.gen_range(0..fitness.len()))
+                        .max_by(|&a, &b| fitness[a].total_cmp(&fitness[b]))
+                        .expect("Tournament size is at least 1.")
+                })
+                .collect(),
+            Self::Rank => {
+                let ranking = ranking(fitness);
+                let dist = WeightedIndex::new(1..=ranking.len()).expect("Population isn't empty.");
+                dist.sample_iter(rng)
+                    .take(n)
+                    .map(|rank| ranking[rank])
+                    .collect()
+            }
+            Self::Truncation { fraction } => {
+                let ranking = ranking(fitness);
+                let n_best = ((ranking.len() as f64 * fraction).ceil() as usize).max(1);
+                let best = &ranking[ranking.len() - n_best..];
+                (0..n).map(|_| best[rng.gen_range(0..best.len())]).collect()
+            }
+        }
+    }
+}
+
+/// Indices of agents from the worst to the best one.
+fn ranking(fitness: &[f64]) -> Vec<usize> {
+    let mut ranking: Vec<usize> = (0..fitness.len()).collect();
+    ranking.sort_by(|&a, &b| fitness[a].total_cmp(&fitness[b]));
+    ranking
+}
+
+impl Display for Selection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Roulette => write!(f, "roulette"),
+            Self::Tournament { k } => write!(f, "tournament:{k}"),
+            Self::Rank => write!(f, "rank"),
+            Self::Truncation { fraction } => write!(f, "truncation:{fraction}"),
+        }
+    }
+}
+
+impl FromStr for Selection {
+    type Err = anyhow::Error;
+
+    /// Parse `roulette`, `tournament[:k]`, `rank` or `truncation[:fraction]`, e.g. `tournament:5`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, param) = match s.split_once(':') {
+            Some((name, param)) => (name, Some(param)),
+            None => (s, None),
+        };
+        let invalid = || format!("Invalid parameter {param:?} of selection {name}.");
+
+        let selection = match (name, param) {
+            ("roulette", None) => Self::Roulette,
+            ("rank", None) => Self::Rank,
+            ("tournament", k) => Self::Tournament {
+                k: k.map_or(Ok(Self::DEFAULT_TOURNAMENT_SIZE), str::parse)
+                    .with_context(invalid)?,
+            },
+            ("truncation", fraction) => Self::Truncation {
+                fraction: fraction
+                    .map_or(Ok(Self::DEFAULT_TRUNCATION_FRACTION), str::parse)
+                    .with_context(invalid)?,
+            },
+            ("roulette" | "rank", Some(_)) => bail!("Selection {name} takes no parameters."),
+            _ => bail!(
+                "Unknown selection {name:?}, expected roulette, tournament, rank or truncation."
+            ),
+        };
+
+        selection.validate()?;
+        Ok(selection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::Selection;
+
+    const FITNESS: [f64; 6] = [10., 0., 50., 20., 40., 30.];
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(
+            "roulette".parse::<Selection>().unwrap(),
+            Selection::Roulette
+        );
+        assert_eq!(
+            "tournament".parse::<Selection>().unwrap(),
+            Selection::Tournament { k: 3 }
+        );
+        assert_eq!(
+            "truncation:0.2".parse::<Selection>().unwrap(),
+            Selection::Truncation { fraction: 0.2 }
+        );
+        let rank = Selection::Rank;
+        assert_eq!(rank.to_string().parse::<Selection>().unwrap(), rank);
+
+        assert!("tournament:0".parse::<Selection>().is_err());
+        assert!("truncation:2".parse::<Selection>().is_err());
+        assert!("rank:2".parse::<Selection>().is_err());
+        assert!("best".parse::<Selection>().is_err());
+    }
+
+    #[test]
+    fn test_truncation_selects_only_best() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let selection = Selection::Truncation { fraction: 0.5 };
+
+        let selected = selection.select(&FITNESS, 100, &mut rng);
+        assert_eq!(selected.len(), 100);
+        assert!(selected.iter().all(|idx| [2, 4, 5].contains(idx)));
+    }
+
+    #[test]
+    fn test_tournament_of_whole_population_selects_best() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // With many draws the best agent is almost surely in every tournament.
+        let selected = Selection::Tournament { k: 100 }.select(&FITNESS, 10, &mut rng);
+        assert!(selected.iter().all(|&idx| idx == 2));
+    }
+
+    #[test]
+    fn test_rank_prefers_better_agents() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let selected = Selection::Rank.select(&FITNESS, 10_000, &mut rng);
+        let count = |idx| selected.iter().filter(|&&s| s == idx).count();
+        // The best agent has rank 6, the worst one rank 1.
+        assert!(count(2) > 4 * count(1));
+        assert!(count(1) > 0);
+    }
+}
@@ -0,0 +1,40 @@
+//! Setup shared by tests of agents, searches and training.
+
+use tetris_core::prelude::*;
+use tetris_heuristics::HeuristicRegistry;
+
+use crate::{Agent, Config, Crossover, Mutation, Optimizer, SearchConfig, Selection};
+
+/// El-Tetris agent playing the game with `seed`, after it made `greedy_moves` greedy moves.
+pub fn el_tetris_agent(seed: u64, greedy_moves: usize) -> Agent {
+    let mut agent = Agent::el_tetris();
+    agent.game = Game::from_seed(seed);
+    for _ in 0..greedy_moves {
+        agent.make_a_move(SearchConfig::GREEDY);
+    }
+    agent
+}
+
+/// Small and quick genetic algorithm, keeping elites and adapting the steps of mutation.
+pub fn training_config() -> Config {
+    Config {
+        n_entities: 6,
+        mutation_rate: 0.1,
+        max_drops: Some(10),
+        max_populations: Some(1),
+        max_non_progress_populations: None,
+        heuristics_used: HeuristicRegistry::default()
+            .select(&["holes", "bumpiness"])
+            .unwrap(),
+        normalization: Vec::new(),
+        search: SearchConfig::GREEDY,
+        mcts: None,
+        network: None,
+        optimizer: Optimizer::Genetic,
+        selection: Selection::Roulette,
+        elite: 2,
+        crossover: Crossover::Blend,
+        mutation: Mutation::SelfAdaptive { sigma: 0.5 },
+        normalize_weights: false,
+    }
+}
@@ -1,41 +1,107 @@
 use anyhow::Result;
 use macroquad::prelude::*;
 use tetris_core::prelude::*;
-use tetris_heuristics::used_heuristics::get_heuristics;
-use tetris_ml::{Agent, BranchingMode};
+use tetris_heuristics::HeuristicRegistry;
+use tetris_ml::{
+    decide, mcts_search, Budget, Evaluator, MctsConfig, SavedModel, SearchCache, SearchConfig,
+};
 const BLOCK_SIZE: f32 = 30.;
 const BORDER: f32 = 2.;
 const GRID_SPACING: f32 = 2.;
 const BOARD_MARGIN: f32 = 5.;
+const HISTORY_CAPACITY: usize = 1000;
+/// Deepest search that can be chosen with the arrow keys, if unseen pieces are searched.
+const MAX_SEARCH_DEPTH: usize = 4;
+
+/// Handcoded solution, which was found during training.
+const HANDCODED_MODEL: [(&str, f32); 5] = [
+    ("bumpiness", 0.153_936_06),
+    ("holes", 0.664_052_07),
+    ("relative_diff", 0.087_044_27),
+    ("highest_block", 0.103_674_956),
+    ("i_clear_potential", -0.382_218_1),
+];
+
+/// Show `model` playing, choosing moves with `mcts` if it's set, or with `search` otherwise,
+/// deepened only as long as `budget` allows.
+pub async fn run(
+    model: Option<SavedModel>,
+    search: SearchConfig,
+    budget: Budget,
+    mcts: Option<MctsConfig>,
+) -> Result<()> {
+    let model = model.unwrap_or_else(|| SavedModel::from_named_weights(&HANDCODED_MODEL));
+    let evaluator = model.to_evaluator(&HeuristicRegistry::default())?;
+
+    watch(&evaluator, Game::new(), search, budget, mcts).await;
+    Ok(())
+}
 
-pub async fn run() -> Result<()> {
-    let mut branching_mode = BranchingMode::Current;
-
-    let mut agent = Agent::from_weights(
-        vec![
-            0.153_936_06,
-            0.664_052_07,
-            0.087_044_27,
-            0.103_674_956,
-            -0.382_218_1,
-        ],
-        &get_heuristics(),
-    )?;
-
-    let game_width = agent.game.width;
-    let game_height = agent.game.height;
+/// Show any `evaluator` playing `game`, like [`run`] does with a saved model.
+pub async fn watch<E: Evaluator + Sync>(
+    evaluator: &E,
+    mut game: Game,
+    mut search: SearchConfig,
+    budget: Budget,
+    mcts: Option<MctsConfig>,
+) {
+    let game_width = game.width;
+    let game_height = game.height;
+
+    let mut history = History::new(Some(HISTORY_CAPACITY));
+    let mut paused = false;
+    // Evaluator never changes while playing, so evaluations are reused between moves.
+    let mut cache = SearchCache::default();
 
     loop {
         clear_background(BLACK);
         draw_background(game_width, game_height);
-        draw_current_state(&agent);
-        show_branching_mode_text(branching_mode);
+        draw_current_state(&game);
+        if mcts.is_some() {
+            draw_text("MCTS", 500., 200., 56., WHITE);
+        } else {
+            show_search_text(search, budget, &cache);
+        }
+        show_history_controls_text(paused);
+
+        if is_key_pressed(KeyCode::Up) {
+            let deeper = SearchConfig {
+                depth: search.depth + 1,
+                ..search
+            };
+            if deeper.depth <= MAX_SEARCH_DEPTH && deeper.validate().is_ok() {
+                search = deeper;
+            }
+        }
+
+        if is_key_pressed(KeyCode::Down) {
+            search.depth = search.depth.saturating_sub(1).max(1);
+        }
+
+        if is_key_pressed(KeyCode::P) {
+            paused = !paused;
+        }
 
-        if is_key_pressed(KeyCode::Space) {
-            branching_mode.toggle();
+        if is_key_pressed(KeyCode::U) {
+            paused = true;
+            history.undo(&mut game);
+        }
+
+        if is_key_pressed(KeyCode::R) {
+            paused = true;
+            history.redo(&mut game);
+        }
+
+        if !paused && !game.is_lost() {
+            let next_state = mcts.map_or_else(
+                || decide(evaluator, &game, search, budget, &mut cache),
+                |config| mcts_search(evaluator, &game, &config),
+            );
+            if let Some(next_state) = next_state {
+                history.advance_to(&mut game, next_state);
+            }
         }
 
-        agent.make_a_move(branching_mode);
         next_frame().await;
     }
 }
@@ -86,16 +152,16 @@ fn draw_tetrimino(x: f32, y: f32, color: Color) {
     );
 }
 
-fn draw_current_state(agent: &Agent) {
-    for (pos, block) in agent.game.board.iter_blocks() {
+fn draw_current_state(game: &Game) {
+    for (pos, block) in game.board.iter_blocks() {
         draw_tetrimino(pos.x as f32, pos.y as f32, get_color_of_block(block));
     }
 
-    for pos in agent.game.piece.iter_blocks() {
+    for pos in game.piece.iter_blocks() {
         draw_tetrimino(
             pos.x as f32,
             pos.y as f32,
-            get_color_of_block(agent.game.piece.block_type),
+            get_color_of_block(game.piece.block_type),
         );
     }
 }
@@ -108,13 +174,28 @@ fn draw_background(width: i32, height: i32) {
     }
 }
 
-fn show_branching_mode_text(branching_mode: BranchingMode) {
-    let mode_text = match branching_mode {
-        BranchingMode::Current => "N=1",
-        BranchingMode::CurrentAndNext => "N=2",
-    };
+fn show_search_text(search: SearchConfig, budget: Budget, cache: &SearchCache) {
+    draw_text(&search.to_string(), 500., 200., 56., WHITE);
+    draw_text("Up/Down - search depth", 500., 235., 28., WHITE);
+    if budget != Budget::Unlimited {
+        draw_text(&format!("Budget: {budget}"), 500., 410., 28., WHITE);
+    }
+
+    let hit_rate = cache.evaluation_stats.hit_rate() * 100.;
+    draw_text(
+        &format!("Cache hits: {hit_rate:.0}%"),
+        500.,
+        380.,
+        28.,
+        WHITE,
+    );
+}
+
+fn show_history_controls_text(paused: bool) {
+    let state_text = if paused { "Paused" } else { "Playing" };
 
-    draw_text(mode_text, 500., 200., 56., WHITE);
+    draw_text(state_text, 500., 280., 40., WHITE);
+    draw_text("P - pause, U - undo, R - redo", 500., 330., 28., WHITE);
 }
 
 const fn get_color_of_block(val: PieceType) -> Color {
@@ -0,0 +1,289 @@
+use tetris_core::prelude::*;
+use tetris_heuristics::HeuristicRegistry;
+use tetris_ml::{decide, Budget, Evaluator, SavedModel, SearchCache, SearchConfig};
+use wasm_bindgen::prelude::*;
+
+/// Moves that a human player can make, mirroring the controls of [`Game`].
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Left,
+    Right,
+    SoftDrop,
+    HardDrop,
+    RotateClockwise,
+    RotateCounterclockwise,
+}
+
+/// Game together with the agent that can play it, exported to JavaScript.
+#[wasm_bindgen]
+pub struct TetrisGame {
+    game: Game,
+    evaluator: Box<dyn Evaluator + Send + Sync>,
+    search: SearchConfig,
+    budget: Budget,
+}
+
+impl std::fmt::Debug for TetrisGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TetrisGame")
+            .field("game", &self.game)
+            .field("search", &self.search)
+            .field("budget", &self.budget)
+            .finish_non_exhaustive()
+    }
+}
+
+// `#[wasm_bindgen]` can't export `const fn`s.
+#[allow(clippy::missing_const_for_fn)]
+#[wasm_bindgen]
+impl TetrisGame {
+    /// Create a new game with pieces determined by `seed`, played by an agent loaded from `model`,
+    /// a JSON model saved by the training, which names the heuristics of its weights.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64, model: &str) -> Result<Self, JsError> {
+        let evaluator = load_evaluator(model).map_err(|err| JsError::new(&format!("{err:#}")))?;
+
+        Ok(Self {
+            game: Game::from_seed(seed),
+            evaluator,
+            search: SearchConfig::default(),
+            budget: Budget::Unlimited,
+        })
+    }
+
+    /// Whether the agent should also take the next piece into account when choosing a move.
+    #[wasm_bindgen(js_name = setLookahead)]
+    pub fn set_lookahead(&mut self, enabled: bool) {
+        self.search = if enabled {
+            SearchConfig::with_depth(2)
+        } else {
+            SearchConfig::GREEDY
+        };
+    }
+
+    /// Let the agent look `depth` pieces ahead, keeping only `beam_width` best boards after each piece
+    /// if it's given. Only the current and the next piece are known, deeper searches need
+    /// `expectimax`, which averages over the pieces past the preview.
+    #[wasm_bindgen(js_name = setSearch)]
+    pub fn set_search(
+        &mut self,
+        depth: usize,
+        beam_width: Option<usize>,
+        expectimax: bool,
+    ) -> Result<(), JsError> {
+        self.search = search_config(depth, beam_width, expectimax)
+            .map_err(|err| JsError::new(&format!("{err:#}")))?;
+        Ok(())
+    }
+
+    /// Let the agent evaluate at most `nodes` boards per move, searching as deep as they allow,
+    /// or search to the full depth if it isn't given.
+    #[wasm_bindgen(js_name = setNodeBudget)]
+    pub fn set_node_budget(&mut self, nodes: Option<usize>) {
+        self.budget = nodes.map_or(Budget::Unlimited, Budget::Nodes);
+    }
+
+    /// Let the agent drop the current piece. Returns `false` if no move could be made.
+    #[wasm_bindgen(js_name = stepAgent)]
+    pub fn step_agent(&mut self) -> bool {
+        if self.game.is_lost() {
+            return false;
+        }
+
+        let mut cache = SearchCache::default();
+        match decide(
+            &self.evaluator,
+            &self.game,
+            self.search,
+            self.budget,
+            &mut cache,
+        ) {
+            Some(next_state) => {
+                self.game = next_state;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply a single move made by a human player.
+    #[wasm_bindgen(js_name = applyAction)]
+    pub fn apply_action(&mut self, action: Action) {
+        if self.game.is_lost() {
+            return;
+        }
+
+        let game = &mut self.game;
+        match action {
+            Action::Left => game.go_left(),
+            Action::Right => game.go_right(),
+            Action::SoftDrop => game.go_down(),
+            Action::HardDrop => game.hard_drop(),
+            Action::RotateClockwise => game.rotate(Rotation::Clockwise),
+            Action::RotateCounterclockwise => game.rotate(Rotation::Counterclockwise),
+        }
+    }
+
+    /// Visible part of the board, including the falling piece, as a flat row-major array
+    /// starting from the top row. Empty cells are `0`, blocks are numbered `1..=7` in the order
+    /// `I, O, T, S, Z, J, L`.
+    #[must_use]
+    pub fn board(&self) -> Vec<u8> {
+        let game = &self.game;
+        let mut cells = vec![0; (game.width * game.height) as usize];
+
+        let mut set = |pos: Coord<i32>, block_type: PieceType| {
+            if (0..game.width).contains(&pos.x) && (0..game.height).contains(&pos.y) {
+                let row = game.height - 1 - pos.y;
+                cells[(row * game.width + pos.x) as usize] = block_code(block_type);
+            }
+        };
+
+        for (pos, block_type) in game.board.iter_blocks() {
+            set(pos.into(), block_type);
+        }
+        for pos in game.piece.iter_blocks() {
+            set(pos, game.piece.block_type);
+        }
+
+        cells
+    }
+
+    #[must_use]
+    pub fn width(&self) -> i32 {
+        self.game.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> i32 {
+        self.game.height
+    }
+
+    #[must_use]
+    pub fn score(&self) -> usize {
+        self.game.score.score
+    }
+
+    #[must_use]
+    #[wasm_bindgen(js_name = clearedRows)]
+    pub fn cleared_rows(&self) -> usize {
+        self.game.score.cleared_rows
+    }
+
+    #[must_use]
+    #[wasm_bindgen(js_name = isLost)]
+    pub fn is_lost(&self) -> bool {
+        self.game.is_lost()
+    }
+}
+
+fn load_evaluator(model: &str) -> anyhow::Result<Box<dyn Evaluator + Send + Sync>> {
+    SavedModel::from_json(model)?.to_evaluator(&HeuristicRegistry::default())
+}
+
+fn search_config(
+    depth: usize,
+    beam_width: Option<usize>,
+    expectimax: bool,
+) -> anyhow::Result<SearchConfig> {
+    let search = SearchConfig {
+        depth,
+        beam_width,
+        ..SearchConfig::default()
+    };
+    let search = if expectimax {
+        search.expectimax()
+    } else {
+        search
+    };
+    search.validate()?;
+    Ok(search)
+}
+
+const fn block_code(block_type: PieceType) -> u8 {
+    match block_type {
+        PieceType::I => 1,
+        PieceType::O => 2,
+        PieceType::T => 3,
+        PieceType::S => 4,
+        PieceType::Z => 5,
+        PieceType::J => 6,
+        PieceType::L => 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use tetris_heuristics::presets;
+    use tetris_ml::SavedModel;
+
+    use super::{load_evaluator, search_config, Action, TetrisGame};
+
+    fn new_game(seed: u64) -> TetrisGame {
+        let model = SavedModel::from_named_weights(&presets::EL_TETRIS);
+        TetrisGame::new(seed, &serde_json::to_string(&model).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_board_contains_only_falling_piece_at_start() {
+        let game = new_game(7);
+        let board = game.board();
+
+        assert_eq!(board.len(), (game.width() * game.height()) as usize);
+        assert!(board.iter().filter(|&&cell| cell != 0).count() <= 4);
+        assert!(board.iter().all(|&cell| cell <= 7));
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_agent_game() {
+        let mut first = new_game(42);
+        let mut second = new_game(42);
+
+        for _ in 0..20 {
+            assert_eq!(first.step_agent(), second.step_agent());
+        }
+
+        assert_eq!(first.board(), second.board());
+        assert_eq!(first.score(), second.score());
+    }
+
+    #[test]
+    fn test_model_with_unknown_heuristic_is_rejected() {
+        let model = r#"{"heuristics": ["holes", "unknown"], "weights": [1.0, 2.0]}"#;
+        assert!(load_evaluator(model).is_err());
+        assert!(load_evaluator("[1.0, 2.0]").is_err());
+    }
+
+    #[test]
+    fn test_network_model_is_played() {
+        let model = r#"{
+            "heuristics": ["holes"],
+            "weights": [1.0, 0.0],
+            "network": {"input": "heuristics", "hidden_layers": []}
+        }"#;
+        let mut game = TetrisGame::new(3, model).unwrap();
+        assert!(game.step_agent());
+    }
+
+    #[test]
+    fn test_search_past_preview_needs_expectimax() {
+        assert!(search_config(2, None, false).is_ok());
+        assert!(search_config(3, Some(8), false).is_err());
+        assert!(search_config(3, Some(8), true).is_ok());
+        assert!(search_config(0, None, true).is_err());
+        assert!(search_config(2, Some(0), false).is_err());
+    }
+
+    #[test]
+    fn test_hard_drop_places_piece_on_bottom() {
+        let mut game = new_game(1);
+        game.apply_action(Action::HardDrop);
+
+        let bottom_row = &game.board()[((game.height() - 1) * game.width()) as usize..];
+        assert!(bottom_row.iter().any(|&cell| cell != 0));
+    }
+}